@@ -0,0 +1,22 @@
+/// A hint about how a range of an I/O object will be read.
+///
+/// Passed to [`ReadAt::advise()`](trait.ReadAt.html#method.advise). Files on
+/// Linux pass these on to
+/// [`posix_fadvise()`](http://man7.org/linux/man-pages/man2/posix_fadvise.2.html),
+/// so the kernel can tune its caching and readahead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Advice {
+    /// No particular pattern, the default.
+    Normal,
+    /// The range will be read in random order, so readahead is wasted.
+    Random,
+    /// The range will be read in order, so aggressive readahead helps.
+    Sequential,
+    /// The range will be read soon, so it should be loaded in the background.
+    WillNeed,
+    /// The range won't be read again soon, so cached pages can be dropped.
+    DontNeed,
+    /// The range will be read once, and not reused.
+    NoReuse,
+}
@@ -0,0 +1,263 @@
+use alloc::{borrow::Cow, boxed::Box, vec::Vec};
+use core::{any::Any, fmt};
+#[cfg(feature = "std")]
+use std::fs::File;
+
+#[cfg(feature = "std")]
+use super::io::{IoSlice, IoSliceMut};
+use super::{Advice, ReadAt, ReadBuf, Size, WriteAt, io};
+
+/// A type-erased I/O object, that remembers which traits it supports.
+///
+/// A `Box<dyn ReadAt>` loses track of whether the value inside can also be
+/// written or knows its size. An `AnyIo` always supports `ReadAt`, and
+/// records the other traits from the type of the value it's built from, so
+/// they can be discovered with [`as_write_at()`](#method.as_write_at) and
+/// [`as_size()`](#method.as_size). [`new()`](#method.new) takes values that
+/// support every trait, while [`new_rw()`](#method.new_rw),
+/// [`new_sized()`](#method.new_sized) and
+/// [`new_read_only()`](#method.new_read_only) take values that support fewer.
+/// The original value can be recovered with [`downcast()`](#method.downcast),
+/// which is why it must be `'static`.
+///
+/// `AnyIo` implements `WriteAt` and `Size` itself too. Writes fail with
+/// `ErrorKind::Unsupported` if the value can't be written, and the size is
+/// unknown if the value doesn't have one.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use positioned_io::{AnyIo, ReadAt};
+///
+/// # fn foo() -> io::Result<()> {
+/// let mut io = AnyIo::new(vec![1, 2, 3]);
+/// if let Some(writer) = io.as_write_at() {
+///     writer.write_all_at(3, &[4])?;
+/// }
+/// assert_eq!(io.as_size().unwrap().size()?, Some(4));
+///
+/// let data: &'static [u8] = b"read-only";
+/// let io = AnyIo::new_sized(data);
+/// assert!(!io.is_writable());
+/// assert_eq!(*io.downcast_ref::<&[u8]>().unwrap(), b"read-only");
+/// # Ok(())
+/// # }
+/// # fn main() { foo().unwrap(); }
+/// ```
+pub struct AnyIo {
+    io: Box<dyn Erased>,
+}
+
+// The wrapped value, along with the traits it supports beyond `ReadAt`.
+// These are captured by the constructors, which know the concrete type.
+struct Erase<I> {
+    io: I,
+    write: Option<fn(&mut I) -> &mut dyn WriteAt>,
+    size: Option<fn(&I) -> &dyn Size>,
+}
+
+trait Erased {
+    fn read(&self) -> &dyn ReadAt;
+    fn write(&mut self) -> Option<&mut dyn WriteAt>;
+    fn is_writable(&self) -> bool;
+    fn size(&self) -> Option<&dyn Size>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<I: ReadAt + 'static> Erased for Erase<I> {
+    fn read(&self) -> &dyn ReadAt {
+        &self.io
+    }
+
+    fn write(&mut self) -> Option<&mut dyn WriteAt> {
+        Some(self.write?(&mut self.io))
+    }
+
+    fn is_writable(&self) -> bool {
+        self.write.is_some()
+    }
+
+    fn size(&self) -> Option<&dyn Size> {
+        Some(self.size?(&self.io))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        &self.io
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.io
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        Box::new(self.io)
+    }
+}
+
+impl AnyIo {
+    /// Wraps a value that supports `ReadAt`, `WriteAt` and `Size`.
+    pub fn new<I: ReadAt + WriteAt + Size + 'static>(io: I) -> AnyIo {
+        AnyIo::erase(Erase {
+            io,
+            write: Some(|io| io),
+            size: Some(|io| io),
+        })
+    }
+
+    /// Wraps a value that supports `ReadAt` and `WriteAt`, but not `Size`.
+    pub fn new_rw<I: ReadAt + WriteAt + 'static>(io: I) -> AnyIo {
+        AnyIo::erase(Erase {
+            io,
+            write: Some(|io| io),
+            size: None,
+        })
+    }
+
+    /// Wraps a read-only value that supports `ReadAt` and `Size`.
+    pub fn new_sized<I: ReadAt + Size + 'static>(io: I) -> AnyIo {
+        AnyIo::erase(Erase {
+            io,
+            write: None,
+            size: Some(|io| io),
+        })
+    }
+
+    /// Wraps a value that only supports `ReadAt`.
+    pub fn new_read_only<I: ReadAt + 'static>(io: I) -> AnyIo {
+        AnyIo::erase(Erase {
+            io,
+            write: None,
+            size: None,
+        })
+    }
+
+    fn erase<I: ReadAt + 'static>(io: Erase<I>) -> AnyIo {
+        AnyIo { io: Box::new(io) }
+    }
+
+    /// Gets the wrapped value as a `ReadAt`.
+    pub fn as_read_at(&self) -> &dyn ReadAt {
+        self.io.read()
+    }
+
+    /// Gets the wrapped value as a `WriteAt`, if it supports writing.
+    pub fn as_write_at(&mut self) -> Option<&mut dyn WriteAt> {
+        self.io.write()
+    }
+
+    /// Gets the wrapped value as a `Size`, if it knows its size.
+    pub fn as_size(&self) -> Option<&dyn Size> {
+        self.io.size()
+    }
+
+    /// Checks whether the wrapped value supports writing.
+    pub fn is_writable(&self) -> bool {
+        self.io.is_writable()
+    }
+
+    /// Checks whether the wrapped value is of type `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        self.io.as_any().is::<T>()
+    }
+
+    /// Gets a reference to the wrapped value, if it's of type `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.io.as_any().downcast_ref()
+    }
+
+    /// Gets a mutable reference to the wrapped value, if it's of type `T`.
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.io.as_any_mut().downcast_mut()
+    }
+
+    /// Unwraps the value, if it's of type `T`, or gives back the `AnyIo`.
+    pub fn downcast<T: Any>(self) -> Result<T, AnyIo> {
+        if !self.is::<T>() {
+            return Err(self);
+        }
+        match self.io.into_any().downcast() {
+            Ok(io) => Ok(*io),
+            Err(_) => unreachable!("AnyIo checked the type of its value"),
+        }
+    }
+
+    fn writer(&mut self) -> io::Result<&mut dyn WriteAt> {
+        self.as_write_at().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "wrapped I/O object is not writable",
+            )
+        })
+    }
+}
+
+impl fmt::Debug for AnyIo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyIo")
+            .field("writable", &self.is_writable())
+            .field("sized", &self.as_size().is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ReadAt for AnyIo {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.as_read_at().read_at(pos, buf)
+    }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.as_read_at().read_vectored_at(pos, bufs)
+    }
+
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        self.as_read_at().read_many_at(requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.as_read_at().read_buf_at(pos, buf)
+    }
+
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        self.as_read_at().read_cow_at(pos, len)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_read_file(&self) -> Option<&File> {
+        self.as_read_at().as_read_file()
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.as_read_at().advise(pos, len, advice)
+    }
+}
+
+impl WriteAt for AnyIo {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.writer()?.write_at(pos, buf)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.writer()?.write_vectored_at(pos, bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.as_write_at() {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Size for AnyIo {
+    fn size(&self) -> io::Result<Option<u64>> {
+        match self.as_size() {
+            Some(size) => size.size(),
+            None => Ok(None),
+        }
+    }
+}
@@ -1,6 +1,12 @@
-use std::{cmp::min, io};
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, boxed::Box, rc::Rc, string::String, vec::Vec};
+use core::cmp::min;
 
-use super::{ReadAt, Size, WriteAt};
+use super::{
+    Capabilities, Durable, Geometry, ReadAt, ReadAtRef, ReadBuf, Size, Sparse, WriteAt, io,
+};
 
 impl ReadAt for &[u8] {
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
@@ -12,6 +18,27 @@ impl ReadAt for &[u8] {
         buf[..bytes].copy_from_slice(&self[pos..(pos + bytes)]);
         Ok(bytes)
     }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        if pos >= self.len() as u64 {
+            return Ok(0);
+        }
+        let pos = pos as usize;
+        let bytes = min(buf.remaining(), self.len() - pos);
+        buf.append(&self[pos..(pos + bytes)]);
+        Ok(bytes)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+impl ReadAtRef for &[u8] {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(borrow_at(self, pos, len))
+    }
 }
 
 impl ReadAt for &mut [u8] {
@@ -19,6 +46,28 @@ impl ReadAt for &mut [u8] {
         let immutable: &[u8] = self;
         immutable.read_at(pos, buf)
     }
+
+    #[cfg(feature = "alloc")]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        let immutable: &[u8] = self;
+        immutable.read_many_at(requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        let immutable: &[u8] = self;
+        immutable.read_buf_at(pos, buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+impl ReadAtRef for &mut [u8] {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(borrow_at(self, pos, len))
+    }
 }
 
 impl WriteAt for &mut [u8] {
@@ -48,3 +97,259 @@ impl Size for &mut [u8] {
         Ok(Some(self.len() as u64))
     }
 }
+
+#[cfg(feature = "alloc")]
+impl ReadAt for Box<[u8]> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let immutable: &[u8] = self;
+        immutable.read_at(pos, buf)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        let immutable: &[u8] = self;
+        immutable.read_buf_at(pos, buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ReadAtRef for Box<[u8]> {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(borrow_at(self, pos, len))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Size for Box<[u8]> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        Ok(Some(self.len() as u64))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl WriteAt for Box<[u8]> {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        let mut mutable: &mut [u8] = self;
+        mutable.write_at(pos, buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl ReadAt for Arc<[u8]> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let immutable: &[u8] = self;
+        immutable.read_at(pos, buf)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        let immutable: &[u8] = self;
+        immutable.read_buf_at(pos, buf)
+    }
+
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl ReadAtRef for Arc<[u8]> {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(borrow_at(self, pos, len))
+    }
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "ptr"))]
+impl Size for Arc<[u8]> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        Ok(Some(self.len() as u64))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ReadAt for Rc<[u8]> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let immutable: &[u8] = self;
+        immutable.read_at(pos, buf)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        let immutable: &[u8] = self;
+        immutable.read_buf_at(pos, buf)
+    }
+
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ReadAtRef for Rc<[u8]> {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(borrow_at(self, pos, len))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Size for Rc<[u8]> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        Ok(Some(self.len() as u64))
+    }
+}
+
+/// Arrays have a fixed size, like `&mut [u8]`: writes past the end are
+/// truncated.
+impl<const N: usize> ReadAt for [u8; N] {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.as_slice().read_at(pos, buf)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.as_slice().read_buf_at(pos, buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+impl<const N: usize> ReadAtRef for [u8; N] {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(borrow_at(self, pos, len))
+    }
+}
+
+impl<const N: usize> WriteAt for [u8; N] {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.as_mut_slice().write_at(pos, buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> Size for [u8; N] {
+    fn size(&self) -> io::Result<Option<u64>> {
+        Ok(Some(N as u64))
+    }
+}
+
+/// Strings are read as their UTF-8 bytes. They can't be written, since that
+/// could leave them invalid.
+impl ReadAt for str {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.as_bytes().read_at(pos, buf)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.as_bytes().read_buf_at(pos, buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+impl ReadAtRef for str {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(borrow_at(self.as_bytes(), pos, len))
+    }
+}
+
+impl Size for str {
+    fn size(&self) -> io::Result<Option<u64>> {
+        Ok(Some(self.len() as u64))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ReadAt for String {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.as_str().read_at(pos, buf)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.as_str().read_buf_at(pos, buf)
+    }
+
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ReadAtRef for String {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(borrow_at(self.as_bytes(), pos, len))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Size for String {
+    fn size(&self) -> io::Result<Option<u64>> {
+        Ok(Some(self.len() as u64))
+    }
+}
+
+impl Geometry for &[u8] {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        Ok(Capabilities::memory(false))
+    }
+}
+
+impl Geometry for &mut [u8] {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        Ok(Capabilities::memory(false))
+    }
+}
+
+impl<const N: usize> Geometry for [u8; N] {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        Ok(Capabilities::memory(false))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Geometry for Box<[u8]> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        Ok(Capabilities::memory(false))
+    }
+}
+
+impl Sparse for &[u8] {}
+
+impl Sparse for &mut [u8] {}
+
+impl Durable for &mut [u8] {
+    fn sync_data(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn sync_range(&self, _pos: u64, _len: u64) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Borrows up to `len` bytes at an offset, stopping at the end of `data`.
+pub(crate) fn borrow_at(data: &[u8], pos: u64, len: usize) -> &[u8] {
+    if pos >= data.len() as u64 {
+        return &[];
+    }
+    let pos = pos as usize;
+    let bytes = min(len, data.len() - pos);
+    &data[pos..(pos + bytes)]
+}
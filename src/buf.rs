@@ -0,0 +1,178 @@
+use core::{cmp::min, fmt, mem::MaybeUninit, ptr};
+
+use super::io;
+
+/// A buffer that may not be fully initialized, for
+/// [`ReadAt::read_buf_at()`](trait.ReadAt.html#method.read_buf_at).
+///
+/// The buffer is split into three parts: bytes that have been filled by
+/// reads, bytes that are initialized but not yet filled, and bytes that are
+/// not initialized at all. Only filled bytes can be read back.
+///
+/// This is similar to the unstable
+/// [`BorrowedBuf`](https://doc.rust-lang.org/std/io/struct.BorrowedBuf.html)
+/// in the standard library.
+pub struct ReadBuf<'a> {
+    buf: &'a mut [MaybeUninit<u8>],
+    filled: usize,
+    // Always at least as large as filled.
+    init: usize,
+}
+
+impl fmt::Debug for ReadBuf<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadBuf")
+            .field("filled", &self.filled)
+            .field("init", &self.init)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+impl<'a> ReadBuf<'a> {
+    /// Creates an empty buffer over memory that may not be initialized.
+    pub fn new(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        ReadBuf {
+            buf,
+            filled: 0,
+            init: 0,
+        }
+    }
+
+    /// Gets the total size of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Gets the number of bytes filled so far.
+    pub fn len(&self) -> usize {
+        self.filled
+    }
+
+    /// Returns true if no bytes have been filled yet.
+    pub fn is_empty(&self) -> bool {
+        self.filled == 0
+    }
+
+    /// Gets the number of bytes that can still be filled.
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.filled
+    }
+
+    /// Gets the bytes filled so far.
+    pub fn filled(&self) -> &[u8] {
+        // Safety: filled bytes are always initialized.
+        unsafe { &*(&self.buf[..self.filled] as *const [MaybeUninit<u8>] as *const [u8]) }
+    }
+
+    /// Gets the bytes filled so far, mutably.
+    pub fn filled_mut(&mut self) -> &mut [u8] {
+        // Safety: filled bytes are always initialized.
+        unsafe { &mut *(&mut self.buf[..self.filled] as *mut [MaybeUninit<u8>] as *mut [u8]) }
+    }
+
+    /// Empties the buffer, so it can be filled again.
+    ///
+    /// Memory stays initialized, so it won't need to be zeroed again.
+    pub fn clear(&mut self) {
+        self.filled = 0;
+    }
+
+    /// Copies bytes to the end of the filled part.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't room for all of `data`.
+    pub fn append(&mut self, data: &[u8]) {
+        assert!(data.len() <= self.remaining(), "buffer overflow");
+        // Safety: the range fits in the buffer, and can't overlap data since
+        // we hold a unique reference.
+        unsafe {
+            let dst = self.buf.as_mut_ptr().add(self.filled) as *mut u8;
+            ptr::copy_nonoverlapping(data.as_ptr(), dst, data.len());
+        }
+        self.filled += data.len();
+        self.init = self.init.max(self.filled);
+    }
+
+    /// Zeroes any memory that isn't initialized yet, and returns the unfilled
+    /// part of the buffer.
+    pub fn initialize_unfilled(&mut self) -> &mut [u8] {
+        for byte in &mut self.buf[self.init..] {
+            byte.write(0);
+        }
+        self.init = self.capacity();
+        // Safety: everything is now initialized.
+        unsafe { &mut *(&mut self.buf[self.filled..] as *mut [MaybeUninit<u8>] as *mut [u8]) }
+    }
+
+    /// Marks `n` more bytes as filled, after writing to them through
+    /// `initialize_unfilled()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if fewer than `n` unfilled bytes are initialized.
+    pub fn advance(&mut self, n: usize) {
+        assert!(
+            n <= self.init - self.filled,
+            "advanced past initialized bytes"
+        );
+        self.filled += n;
+    }
+
+    /// Returns the unfilled part of the buffer, which may not be initialized.
+    ///
+    /// # Safety
+    ///
+    /// The caller must not de-initialize any bytes, for example by writing
+    /// `MaybeUninit::uninit()`.
+    pub unsafe fn unfilled_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        &mut self.buf[self.filled..]
+    }
+
+    // Identifies the memory behind this buffer.
+    //
+    // An implementation of `read_buf_at()` can replace the `ReadBuf` it's
+    // given with one over other memory, so unsafe code that trusts `len()`
+    // afterwards must check that the memory is still the same.
+    pub(crate) fn memory(&self) -> (*const MaybeUninit<u8>, usize) {
+        (self.buf.as_ptr(), self.buf.len())
+    }
+
+    // Fails if this buffer no longer covers `memory`.
+    pub(crate) fn check_memory(&self, memory: (*const MaybeUninit<u8>, usize)) -> io::Result<()> {
+        if self.memory() == memory {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "read_buf_at() replaced the buffer it was given",
+            ))
+        }
+    }
+
+    /// Marks `n` more bytes as filled, after writing to them through
+    /// `unfilled_mut()`.
+    ///
+    /// # Safety
+    ///
+    /// The first `n` unfilled bytes must have been initialized.
+    pub unsafe fn assume_filled(&mut self, n: usize) {
+        self.filled = min(self.filled.saturating_add(n), self.capacity());
+        self.init = self.init.max(self.filled);
+    }
+}
+
+impl<'a> From<&'a mut [u8]> for ReadBuf<'a> {
+    /// Creates an empty buffer over memory that is already initialized.
+    fn from(buf: &'a mut [u8]) -> Self {
+        let init = buf.len();
+        // Safety: we never write uninitialized bytes into the buffer.
+        let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+        ReadBuf {
+            buf,
+            filled: 0,
+            init,
+        }
+    }
+}
@@ -1,12 +1,22 @@
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, vec::Vec};
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
 use std::{
-    io,
+    fs::File,
     io::{Read, Write},
-    marker::PhantomData,
 };
 
-use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt};
+use byteorder::ByteOrder;
+#[cfg(feature = "std")]
+use byteorder::{ReadBytesExt, WriteBytesExt};
 
-use super::{ReadAt, WriteAt};
+#[cfg(feature = "std")]
+use super::io::{IoSlice, IoSliceMut};
+use super::{
+    Advice, Durable, ErrorType, ReadAt, ReadAtRef, ReadBuf, TryReadAt, TryWriteAt, WriteAt, error,
+    io,
+};
 
 /// Extends `ReadAt` with methods for reading numbers at offsets.
 ///
@@ -339,6 +349,7 @@ impl<I, E: ByteOrder> ByteIo<I, E> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<I: Read, E: ByteOrder> Read for ByteIo<I, E> {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
@@ -346,6 +357,7 @@ impl<I: Read, E: ByteOrder> Read for ByteIo<I, E> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<I: Write, E: ByteOrder> Write for ByteIo<I, E> {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
@@ -361,14 +373,71 @@ impl<I: Write, E: ByteOrder> Write for ByteIo<I, E> {
 impl<I: ReadAt, E: ByteOrder> ReadAt for ByteIo<I, E> {
     #[inline]
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
-        self.io.read_at(pos, buf)
+        self.io
+            .read_at(pos, buf)
+            .map_err(|e| error::context(e, "ByteIo::read_at", pos, buf.len() as u64, 0))
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.io.read_vectored_at(pos, bufs)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        self.io.read_many_at(requests)
+    }
+
+    #[inline]
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.io.read_buf_at(pos, buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        self.io.read_cow_at(pos, len)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn as_read_file(&self) -> Option<&File> {
+        self.io.as_read_file()
+    }
+
+    #[inline]
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.io.advise(pos, len, advice)
+    }
+}
+
+impl<I: ReadAtRef, E: ByteOrder> ReadAtRef for ByteIo<I, E> {
+    #[inline]
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        self.io.read_ref_at(pos, len)
     }
 }
 
 impl<I: WriteAt, E: ByteOrder> WriteAt for ByteIo<I, E> {
     #[inline]
     fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
-        self.io.write_at(pos, buf)
+        self.io
+            .write_at(pos, buf)
+            .map_err(|e| error::context(e, "ByteIo::write_at", pos, buf.len() as u64, 0))
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.io.write_vectored_at(pos, bufs)
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn as_write_file(&self) -> Option<&File> {
+        self.io.as_write_file()
     }
 
     #[inline]
@@ -377,6 +446,57 @@ impl<I: WriteAt, E: ByteOrder> WriteAt for ByteIo<I, E> {
     }
 }
 
+impl<I: ErrorType, E: ByteOrder> ErrorType for ByteIo<I, E> {
+    type Error = I::Error;
+}
+
+impl<I: TryReadAt, E: ByteOrder> TryReadAt for ByteIo<I, E> {
+    #[inline]
+    fn try_read_at(&self, pos: u64, buf: &mut [u8]) -> Result<usize, I::Error> {
+        self.io.try_read_at(pos, buf)
+    }
+
+    #[inline]
+    fn try_read_exact_at(&self, pos: u64, buf: &mut [u8]) -> Result<(), I::Error> {
+        self.io.try_read_exact_at(pos, buf)
+    }
+}
+
+impl<I: TryWriteAt, E: ByteOrder> TryWriteAt for ByteIo<I, E> {
+    #[inline]
+    fn try_write_at(&mut self, pos: u64, buf: &[u8]) -> Result<usize, I::Error> {
+        self.io.try_write_at(pos, buf)
+    }
+
+    #[inline]
+    fn try_write_all_at(&mut self, pos: u64, buf: &[u8]) -> Result<(), I::Error> {
+        self.io.try_write_all_at(pos, buf)
+    }
+
+    #[inline]
+    fn try_flush(&mut self) -> Result<(), I::Error> {
+        self.io.try_flush()
+    }
+}
+
+impl<I: Durable, E: ByteOrder> Durable for ByteIo<I, E> {
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        self.io.sync_data()
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        self.io.sync_all()
+    }
+
+    #[inline]
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        self.io.sync_range(pos, len)
+    }
+}
+
+#[cfg(feature = "std")]
 impl<I: Read, E: ByteOrder> ByteIo<I, E> {
     #[inline]
     pub fn read_u8(&mut self) -> io::Result<u8> {
@@ -439,6 +559,7 @@ impl<I: Read, E: ByteOrder> ByteIo<I, E> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<I: Write, E: ByteOrder> ByteIo<I, E> {
     #[inline]
     pub fn write_u8(&mut self, n: u8) -> io::Result<()> {
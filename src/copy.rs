@@ -0,0 +1,258 @@
+use std::{cmp::min, fs::File, io};
+
+use super::{ReadAt, WriteAt, error};
+
+// Size of the buffer used when copying.
+const BUF_SIZE: usize = 64 * 1024;
+
+/// Copies a range of bytes from one I/O object to another, returning the
+/// number of bytes copied.
+///
+/// Copying stops early if the end of `src` is reached, otherwise exactly
+/// `len` bytes are copied.
+///
+/// When both `src` and `dst` are backed by files (see
+/// [`ReadAt::as_read_file()`](trait.ReadAt.html#method.as_read_file)), the copy is
+/// handed off to the kernel on Linux. First the range is shared with a
+/// [reflink](http://man7.org/linux/man-pages/man2/ioctl_ficlonerange.2.html),
+/// if the file system supports that, and otherwise it's copied with
+/// [`copy_file_range()`](http://man7.org/linux/man-pages/man2/copy_file_range.2.html).
+/// Either way the data never passes through userspace. All other objects and
+/// platforms use a buffered loop of `read_at()` and `write_all_at()`.
+///
+/// On Unix, `src` and `dst` may be the same file, even through different
+/// handles. If the ranges overlap, the result is as if the source range was
+/// read in full before anything was written, like `memmove()`: when `dst_pos`
+/// is after `src_pos`, the copy goes backwards from the end of the range.
+/// Elsewhere, or for objects that aren't backed by files, overlapping ranges
+/// of the same storage are copied forwards, which corrupts the overlap when
+/// `dst_pos` is after `src_pos`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// use positioned_io::{copy_range, RandomAccessFile};
+///
+/// # fn foo() -> io::Result<()> {
+/// let src = RandomAccessFile::open("src.img")?;
+/// let mut dst = RandomAccessFile::try_new(std::fs::File::create("dst.img")?)?;
+///
+/// // Copy the second megabyte of src to the start of dst.
+/// let copied = copy_range(&src, 1 << 20, &mut dst, 0, 1 << 20)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn copy_range<R, W>(
+    src: &R,
+    src_pos: u64,
+    dst: &mut W,
+    dst_pos: u64,
+    len: u64,
+) -> io::Result<u64>
+where
+    R: ReadAt + ?Sized,
+    W: WriteAt + ?Sized,
+{
+    if len == 0 {
+        return Ok(0);
+    }
+
+    if let (Some(src_file), Some(dst_file)) = (src.as_read_file(), dst.as_write_file()) {
+        if overlaps(src_file, src_pos, dst_file, dst_pos, len)? {
+            // The kernel refuses overlapping copies within a file.
+            if dst_pos > src_pos {
+                let size = src_file.metadata()?.len();
+                return copy_backwards(src, src_pos, dst, dst_pos, len, size);
+            }
+            return copy_buffered(src, src_pos, dst, dst_pos, len);
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            // The kernel would fail with EBADF, rather than the error that
+            // write_at() gives.
+            crate::unix::check_append(dst_file)?;
+            if let Some(copied) = linux::copy_file(src_file, src_pos, dst_file, dst_pos, len)? {
+                return Ok(copied);
+            }
+        }
+    }
+
+    copy_buffered(src, src_pos, dst, dst_pos, len)
+}
+
+// Checks whether two ranges are in the same file, and overlap.
+fn overlaps(src: &File, src_pos: u64, dst: &File, dst_pos: u64, len: u64) -> io::Result<bool> {
+    if src_pos >= dst_pos.saturating_add(len) || dst_pos >= src_pos.saturating_add(len) {
+        return Ok(false);
+    }
+    same_file(src, dst)
+}
+
+#[cfg(unix)]
+fn same_file(a: &File, b: &File) -> io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let (a, b) = (a.metadata()?, b.metadata()?);
+    Ok(a.dev() == b.dev() && a.ino() == b.ino())
+}
+
+#[cfg(not(unix))]
+fn same_file(_a: &File, _b: &File) -> io::Result<bool> {
+    Ok(false)
+}
+
+/// Copies bytes through a buffer starting from the end of the range, so that
+/// a source range that overlaps the start of the destination is read before
+/// it's overwritten. Only copies up to `size`, the end of the source.
+fn copy_backwards<R, W>(
+    src: &R,
+    src_pos: u64,
+    dst: &mut W,
+    dst_pos: u64,
+    len: u64,
+    size: u64,
+) -> io::Result<u64>
+where
+    R: ReadAt + ?Sized,
+    W: WriteAt + ?Sized,
+{
+    let len = min(len, size.saturating_sub(src_pos));
+    if dst_pos.checked_add(len).is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "position overflows u64",
+        ));
+    }
+    let mut buf = vec![0; min(len, BUF_SIZE as u64) as usize];
+    let mut remaining = len;
+    while remaining > 0 {
+        let n = min(remaining, buf.len() as u64);
+        remaining -= n;
+        let chunk = &mut buf[..n as usize];
+        src.read_exact_at(src_pos + remaining, chunk)?;
+        dst.write_all_at(dst_pos + remaining, chunk)?;
+    }
+    Ok(len)
+}
+
+/// Copies bytes between two offsets through a buffer, returning how many were
+/// copied. Stops early at the end of the source.
+pub(crate) fn copy_buffered<R, W>(
+    src: &R,
+    mut src_pos: u64,
+    dst: &mut W,
+    mut dst_pos: u64,
+    len: u64,
+) -> io::Result<u64>
+where
+    R: ReadAt + ?Sized,
+    W: WriteAt + ?Sized,
+{
+    let mut buf = vec![0; min(len, BUF_SIZE as u64) as usize];
+    let mut copied = 0;
+    while copied < len {
+        let want = min(len - copied, buf.len() as u64) as usize;
+        let bytes = match src.read_at(src_pos, &mut buf[..want]) {
+            Ok(0) => break,
+            Ok(bytes) => bytes,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        dst.write_all_at(dst_pos, &buf[..bytes])?;
+        src_pos = error::pos_after(src_pos, bytes)?;
+        dst_pos = error::pos_after(dst_pos, bytes)?;
+        copied += bytes as u64;
+    }
+    Ok(copied)
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{fs::File, io, os::unix::io::AsRawFd};
+
+    // Keep each request well below the limits of the system call.
+    const MAX_CHUNK: u64 = 1 << 30;
+
+    fn offset(pos: u64) -> io::Result<libc::loff_t> {
+        libc::loff_t::try_from(pos)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset too large"))
+    }
+
+    /// Copies between files in the kernel.
+    ///
+    /// Returns `Ok(None)` if nothing was copied because the kernel can't do
+    /// this copy, so the caller should fall back to a buffered copy.
+    pub(super) fn copy_file(
+        src: &File,
+        src_pos: u64,
+        dst: &File,
+        dst_pos: u64,
+        len: u64,
+    ) -> io::Result<Option<u64>> {
+        if clone_range(src, src_pos, dst, dst_pos, len) {
+            return Ok(Some(len));
+        }
+
+        let mut copied = 0;
+        while copied < len {
+            // Positions past u64::MAX are too large as well.
+            let mut off_in = offset(src_pos.saturating_add(copied))?;
+            let mut off_out = offset(dst_pos.saturating_add(copied))?;
+            let want = (len - copied).min(MAX_CHUNK) as usize;
+            let ret = unsafe {
+                libc::copy_file_range(
+                    src.as_raw_fd(),
+                    &mut off_in,
+                    dst.as_raw_fd(),
+                    &mut off_out,
+                    want,
+                    0,
+                )
+            };
+            if ret == 0 {
+                break;
+            } else if ret > 0 {
+                copied += ret as u64;
+                continue;
+            }
+
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::EINTR) => {}
+                // Not supported for these files, eg: across file systems on
+                // older kernels, or for special files.
+                Some(libc::ENOSYS)
+                | Some(libc::EXDEV)
+                | Some(libc::EINVAL)
+                | Some(libc::EOPNOTSUPP)
+                | Some(libc::EPERM)
+                    if copied == 0 =>
+                {
+                    return Ok(None);
+                }
+                _ => return Err(err),
+            }
+        }
+        Ok(Some(copied))
+    }
+
+    /// Tries to share a range between files with a reflink, returning whether
+    /// it worked.
+    fn clone_range(src: &File, src_pos: u64, dst: &File, dst_pos: u64, len: u64) -> bool {
+        // The whole range must be in the source file, or the clone fails.
+        // Checking up front lets short copies be reported correctly.
+        match src.metadata() {
+            Ok(md) if src_pos.checked_add(len).is_some_and(|end| end <= md.len()) => {}
+            _ => return false,
+        }
+        let range = libc::file_clone_range {
+            src_fd: src.as_raw_fd() as i64,
+            src_offset: src_pos,
+            src_length: len,
+            dest_offset: dst_pos,
+        };
+        unsafe { libc::ioctl(dst.as_raw_fd(), libc::FICLONERANGE, &range) == 0 }
+    }
+}
@@ -1,9 +1,10 @@
 use std::{
+    borrow::Cow,
     io,
-    io::{Read, Seek, SeekFrom, Write},
+    io::{IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write},
 };
 
-use super::{ReadAt, Size, WriteAt};
+use super::{Advice, Durable, ReadAt, ReadBuf, Size, TryReadAt, TryWriteAt, WriteAt, error};
 
 /// Adapts a `ReadAt` or `WriteAt` into a `Read` or `Write`.
 ///
@@ -109,21 +110,12 @@ impl<I> Seek for Cursor<I> {
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
         match pos {
             SeekFrom::Start(p) => self.pos = p,
-            SeekFrom::Current(p) => {
-                let pos = self.pos as i64 + p;
-                if pos < 0 {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        "seek to a negative position",
-                    ));
-                }
-                self.pos = pos as u64;
-            }
+            SeekFrom::Current(p) => self.pos = seek_offset(self.pos, p)?,
             SeekFrom::End(_) => {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
                     "seek from unknown end",
-                ))
+                ));
             }
         }
         Ok(self.pos)
@@ -132,8 +124,20 @@ impl<I> Seek for Cursor<I> {
 
 impl<I: ReadAt> Read for Cursor<I> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        let bytes = self.get_ref().read_at(self.pos, buf)?;
-        self.pos += bytes as u64;
+        // Check before reading, so no data is lost.
+        advance(self.pos, buf.len())?;
+        let bytes = self
+            .get_ref()
+            .read_at(self.pos, buf)
+            .map_err(|e| error::context(e, "Cursor::read", self.pos, buf.len() as u64, 0))?;
+        self.pos = advance(self.pos, bytes)?;
+        Ok(bytes)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        advance(self.pos, total_len(bufs.iter().map(|b| b.len())))?;
+        let bytes = self.get_ref().read_vectored_at(self.pos, bufs)?;
+        self.pos = advance(self.pos, bytes)?;
         Ok(bytes)
     }
 }
@@ -141,8 +145,20 @@ impl<I: ReadAt> Read for Cursor<I> {
 impl<I: WriteAt> Write for Cursor<I> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         let pos = self.pos;
-        let bytes = self.get_mut().write_at(pos, buf)?;
-        self.pos += bytes as u64;
+        advance(pos, buf.len())?;
+        let bytes = self
+            .get_mut()
+            .write_at(pos, buf)
+            .map_err(|e| error::context(e, "Cursor::write", pos, buf.len() as u64, 0))?;
+        self.pos = advance(self.pos, bytes)?;
+        Ok(bytes)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let pos = self.pos;
+        advance(pos, total_len(bufs.iter().map(|b| b.len())))?;
+        let bytes = self.get_mut().write_vectored_at(pos, bufs)?;
+        self.pos = advance(self.pos, bytes)?;
         Ok(bytes)
     }
 
@@ -152,6 +168,71 @@ impl<I: WriteAt> Write for Cursor<I> {
     }
 }
 
+/// Reading from objects with their own error type.
+impl<I: TryReadAt> Cursor<I> {
+    /// Reads bytes at the current position, advancing it.
+    ///
+    /// Like `Read::read()`, but errors from the underlying
+    /// [`TryReadAt`](trait.TryReadAt.html) are returned as they are.
+    pub fn try_read(&mut self, buf: &mut [u8]) -> Result<usize, I::Error> {
+        advance(self.pos, buf.len()).map_err(|e| e.kind())?;
+        let bytes = self.io.try_read_at(self.pos, buf)?;
+        self.pos = advance(self.pos, bytes).map_err(|e| e.kind())?;
+        Ok(bytes)
+    }
+
+    /// Reads exactly enough bytes to fill `buf`, advancing the position.
+    ///
+    /// On error the position is left unchanged.
+    pub fn try_read_exact(&mut self, buf: &mut [u8]) -> Result<(), I::Error> {
+        let end = advance(self.pos, buf.len()).map_err(|e| e.kind())?;
+        self.io.try_read_exact_at(self.pos, buf)?;
+        self.pos = end;
+        Ok(())
+    }
+}
+
+/// Writing to objects with their own error type.
+impl<I: TryWriteAt> Cursor<I> {
+    /// Writes bytes at the current position, advancing it.
+    ///
+    /// Like `Write::write()`, but errors from the underlying
+    /// [`TryWriteAt`](trait.TryWriteAt.html) are returned as they are.
+    pub fn try_write(&mut self, buf: &[u8]) -> Result<usize, I::Error> {
+        advance(self.pos, buf.len()).map_err(|e| e.kind())?;
+        let bytes = self.io.try_write_at(self.pos, buf)?;
+        self.pos = advance(self.pos, bytes).map_err(|e| e.kind())?;
+        Ok(bytes)
+    }
+
+    /// Writes all of `buf`, advancing the position.
+    ///
+    /// On error the position is left unchanged.
+    pub fn try_write_all(&mut self, buf: &[u8]) -> Result<(), I::Error> {
+        let end = advance(self.pos, buf.len()).map_err(|e| e.kind())?;
+        self.io.try_write_all_at(self.pos, buf)?;
+        self.pos = end;
+        Ok(())
+    }
+}
+
+impl<I: Durable> Durable for Cursor<I> {
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        self.get_ref().sync_data()
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        self.get_ref().sync_all()
+    }
+
+    #[inline]
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        self.get_ref().sync_range(pos, len)
+    }
+}
+
 /// Adapts a `ReadAt` or `WriteAt` into a `Read` or `Write`, with better
 /// seeking.
 ///
@@ -237,6 +318,11 @@ impl<I: Size + ReadAt> Read for SizeCursor<I> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         self.cursor.read(buf)
     }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.cursor.read_vectored(bufs)
+    }
 }
 
 impl<I: Size + WriteAt> Write for SizeCursor<I> {
@@ -245,30 +331,119 @@ impl<I: Size + WriteAt> Write for SizeCursor<I> {
         self.cursor.write(buf)
     }
 
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.cursor.write_vectored(bufs)
+    }
+
     #[inline]
     fn flush(&mut self) -> io::Result<()> {
         self.cursor.flush()
     }
 }
 
+impl<I: Size + Durable> Durable for SizeCursor<I> {
+    #[inline]
+    fn sync_data(&self) -> io::Result<()> {
+        self.cursor.sync_data()
+    }
+
+    #[inline]
+    fn sync_all(&self) -> io::Result<()> {
+        self.cursor.sync_all()
+    }
+
+    #[inline]
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        self.cursor.sync_range(pos, len)
+    }
+}
+
 // We know how to seek from the end for SizeCursor.
 impl<I: Size> Seek for SizeCursor<I> {
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
         let pos = match pos {
-            SeekFrom::Start(p) => p as i64,
-            SeekFrom::Current(p) => self.cursor.pos as i64 + p,
+            SeekFrom::Start(p) => p,
+            SeekFrom::Current(p) => seek_offset(self.cursor.pos, p)?,
             SeekFrom::End(p) => match self.get_ref().size() {
                 Err(e) => return Err(e),
                 Ok(None) => {
                     return Err(io::Error::new(
                         io::ErrorKind::InvalidData,
                         "seek from unknown end",
-                    ))
+                    ));
                 }
-                Ok(Some(s)) => s as i64 + p,
+                Ok(Some(s)) => seek_offset(s, p)?,
             },
         };
-        self.cursor.pos = pos as u64;
+        self.cursor.pos = pos;
         Ok(self.cursor.pos)
     }
 }
+
+// Applies a relative seek, without wrapping around.
+fn seek_offset(pos: u64, offset: i64) -> io::Result<u64> {
+    match pos.checked_add_signed(offset) {
+        Some(pos) => Ok(pos),
+        None if offset < 0 => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "seek to a negative position",
+        )),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "seek position overflow",
+        )),
+    }
+}
+
+// Moves the position past the bytes just transferred. Called with the size of
+// the buffers before a transfer, to fail before anything happens.
+fn advance(pos: u64, bytes: usize) -> io::Result<u64> {
+    pos.checked_add(bytes as u64)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position overflow"))
+}
+
+// Adds up the lengths of vectored buffers.
+fn total_len(lens: impl Iterator<Item = usize>) -> usize {
+    lens.fold(0, usize::saturating_add)
+}
+
+/// A standard library cursor is read and written at offsets into the data it
+/// holds, ignoring and leaving alone its current position.
+impl<T: ReadAt> ReadAt for io::Cursor<T> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.get_ref().read_at(pos, buf)
+    }
+
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        self.get_ref().read_many_at(requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.get_ref().read_buf_at(pos, buf)
+    }
+
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        self.get_ref().read_cow_at(pos, len)
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.get_ref().advise(pos, len, advice)
+    }
+}
+
+impl<T: WriteAt> WriteAt for io::Cursor<T> {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.get_mut().write_at(pos, buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.get_mut().flush()
+    }
+}
+
+impl<T: Size> Size for io::Cursor<T> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        self.get_ref().size()
+    }
+}
@@ -0,0 +1,178 @@
+#[cfg(feature = "error-context")]
+use std::{error, fmt};
+
+use super::io;
+
+/// An I/O error, together with where it happened.
+///
+/// With the `error-context` feature enabled, the default methods of `ReadAt`
+/// and `WriteAt`, as well as the `Slice`, `Cursor` and `ByteIo` adapters, wrap
+/// errors in a `PositionedError` before passing them on. Each layer that an
+/// error passes through adds another `PositionedError`, and the chain can be
+/// followed with [`Error::source()`](https://doc.rust-lang.org/std/error/trait.Error.html#method.source).
+///
+/// Errors are still returned as `io::Error`, with the same
+/// [`kind()`](https://doc.rust-lang.org/std/io/struct.Error.html#method.kind)
+/// as the original, so existing error handling keeps working.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use positioned_io::{PositionedError, ReadAt, Slice};
+///
+/// let data = vec![0; 10];
+/// let slice = Slice::new(&data, 4, None);
+/// let mut buf = [0; 8];
+/// let err = slice.read_exact_at(2, &mut buf).unwrap_err();
+/// assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+///
+/// let context = PositionedError::find(&err).unwrap();
+/// assert_eq!(context.layer(), "ReadAt::read_exact_at");
+/// assert_eq!((context.pos(), context.len()), (2, 8));
+/// assert_eq!(context.transferred(), 4);
+/// ```
+#[cfg(feature = "error-context")]
+#[derive(Debug)]
+pub struct PositionedError {
+    layer: &'static str,
+    pos: u64,
+    len: u64,
+    transferred: u64,
+    source: io::Error,
+}
+
+#[cfg(feature = "error-context")]
+impl PositionedError {
+    /// Creates an error for an operation on `len` bytes at `pos`, that failed
+    /// after transferring some bytes.
+    ///
+    /// `layer` describes where the error happened, for example
+    /// `"Slice::read_at"`.
+    pub fn new(
+        source: io::Error,
+        layer: &'static str,
+        pos: u64,
+        len: u64,
+        transferred: u64,
+    ) -> PositionedError {
+        PositionedError {
+            layer,
+            pos,
+            len,
+            transferred,
+            source,
+        }
+    }
+
+    /// Finds the outermost `PositionedError` inside an `io::Error`.
+    pub fn find(err: &io::Error) -> Option<&PositionedError> {
+        err.get_ref()?.downcast_ref()
+    }
+
+    /// Extracts the outermost `PositionedError` from an `io::Error`, or gives
+    /// the error back if it doesn't have one.
+    pub fn downcast(err: io::Error) -> Result<PositionedError, io::Error> {
+        err.downcast()
+    }
+
+    /// Gets a description of where the error happened.
+    pub fn layer(&self) -> &'static str {
+        self.layer
+    }
+
+    /// Gets the offset of the operation, as seen by this layer.
+    ///
+    /// Layers like `Slice` translate offsets, so this is the offset in the
+    /// underlying object.
+    pub fn pos(&self) -> u64 {
+        self.pos
+    }
+
+    /// Gets the number of bytes the operation asked for.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if the operation asked for no bytes at all.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets the number of bytes transferred before the error.
+    pub fn transferred(&self) -> u64 {
+        self.transferred
+    }
+
+    /// Gets the kind of the underlying error.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.source.kind()
+    }
+
+    /// Gets the underlying error, which may be another `PositionedError`.
+    pub fn get_ref(&self) -> &io::Error {
+        &self.source
+    }
+
+    /// Unwraps the underlying error.
+    pub fn into_inner(self) -> io::Error {
+        self.source
+    }
+}
+
+#[cfg(feature = "error-context")]
+impl fmt::Display for PositionedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} bytes at offset {} failed after {} bytes: {}",
+            self.layer, self.len, self.pos, self.transferred, self.source
+        )
+    }
+}
+
+#[cfg(feature = "error-context")]
+impl error::Error for PositionedError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[cfg(feature = "error-context")]
+impl From<PositionedError> for io::Error {
+    fn from(err: PositionedError) -> io::Error {
+        io::Error::new(err.kind(), err)
+    }
+}
+
+/// Adds context to an error, if the `error-context` feature is enabled.
+#[cfg(feature = "error-context")]
+#[cold]
+pub(crate) fn context(
+    err: io::Error,
+    layer: &'static str,
+    pos: u64,
+    len: u64,
+    transferred: u64,
+) -> io::Error {
+    PositionedError::new(err, layer, pos, len, transferred).into()
+}
+
+#[cfg(not(feature = "error-context"))]
+#[inline(always)]
+pub(crate) fn context(
+    err: io::Error,
+    _layer: &'static str,
+    _pos: u64,
+    _len: u64,
+    _transferred: u64,
+) -> io::Error {
+    err
+}
+
+/// Gets the position `n` bytes after `pos`, failing instead of wrapping around
+/// past `u64::MAX`.
+pub(crate) fn pos_after(pos: u64, n: usize) -> io::Result<u64> {
+    pos.checked_add(n as u64)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "position overflows u64"))
+}
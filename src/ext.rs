@@ -0,0 +1,176 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use super::Size;
+use super::{ReadAt, error, io};
+
+// How much to read at a time when the size of a source is unknown.
+#[cfg(feature = "alloc")]
+const CHUNK_LEN: usize = 8192;
+
+// How much to read when checking whether a source has more data.
+#[cfg(feature = "alloc")]
+const PROBE_LEN: usize = 32;
+
+/// Extends `ReadAt` with convenience methods for common kinds of reads.
+///
+/// This is implemented for every `ReadAt`, so it only needs to be imported.
+///
+/// # Examples
+///
+/// Read a header, then the table it points to:
+///
+/// ```
+/// # use std::io;
+/// use positioned_io::ReadAtExt;
+///
+/// # fn foo() -> io::Result<()> {
+/// let data: &[u8] = &[4, 2, 0, 0, 7, 8, 9];
+/// let [offset, len] = data.read_array_at(0)?;
+/// let table = data.read_vec_at(u64::from(offset), usize::from(len))?;
+/// assert_eq!(table, [7, 8]);
+///
+/// let mut buf = [0; 8];
+/// assert_eq!(data.read_at_most(5, &mut buf)?, 2);
+/// # Ok(())
+/// # }
+/// # fn main() { foo().unwrap(); }
+/// ```
+pub trait ReadAtExt: ReadAt {
+    /// Reads as many bytes as are available at an offset, up to the length of
+    /// `buf`, returning how many bytes were read.
+    ///
+    /// Unlike [`read_at()`](trait.ReadAt.html#tymethod.read_at), this keeps
+    /// reading until the buffer is full, so fewer bytes are returned only at
+    /// the "end of file". Unlike
+    /// [`read_exact_at()`](trait.ReadAt.html#method.read_exact_at), reaching
+    /// the end isn't an error.
+    fn read_at_most(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let mut total = 0;
+        while total < buf.len() {
+            let read = match error::pos_after(pos, total) {
+                Ok(pos) => self.read_at(pos, &mut buf[total..]),
+                Err(e) => Err(e),
+            };
+            match read {
+                Ok(0) => break,
+                Ok(n) => total += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    let len = buf.len() as u64;
+                    return Err(error::context(
+                        e,
+                        "ReadAtExt::read_at_most",
+                        pos,
+                        len,
+                        total as u64,
+                    ));
+                }
+            }
+        }
+        Ok(total)
+    }
+
+    /// Reads exactly `N` bytes from an offset into an array.
+    ///
+    /// Errors with `ErrorKind::UnexpectedEof` if the "end of file" is
+    /// encountered first.
+    fn read_array_at<const N: usize>(&self, pos: u64) -> io::Result<[u8; N]> {
+        let mut buf = [0; N];
+        self.read_exact_at(pos, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Reads exactly `len` bytes from an offset into a new vector.
+    ///
+    /// Errors with `ErrorKind::UnexpectedEof` if the "end of file" is
+    /// encountered first, or `ErrorKind::OutOfMemory` if the vector can't be
+    /// allocated.
+    #[cfg(feature = "alloc")]
+    fn read_vec_at(&self, pos: u64, len: usize) -> io::Result<Vec<u8>> {
+        let mut vec = Vec::new();
+        crate::vec::reserve(&mut vec, len)?;
+        let n = self.read_to_vec_at(pos, len, &mut vec)?;
+        if n < len {
+            return Err(error::context(
+                io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer"),
+                "ReadAtExt::read_vec_at",
+                pos,
+                len as u64,
+                n as u64,
+            ));
+        }
+        Ok(vec)
+    }
+
+    /// Reads everything from an offset to the "end of file", appending it to
+    /// a vector and returning how many bytes were read.
+    ///
+    /// The vector grows as needed, like with
+    /// [`Read::read_to_end()`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_to_end).
+    /// If an error occurs, any bytes read so far are still appended.
+    #[cfg(feature = "alloc")]
+    fn read_to_end_at(&self, pos: u64, vec: &mut Vec<u8>) -> io::Result<usize> {
+        read_to_end(self, pos, 0, vec)
+    }
+
+    /// Like [`read_to_end_at()`](#method.read_to_end_at), but uses the size
+    /// of the source to allocate space up front.
+    ///
+    /// The size is only a hint: reading continues until the end is actually
+    /// reached, so a source that grows in the meantime is read in full.
+    #[cfg(feature = "alloc")]
+    fn read_to_end_sized_at(&self, pos: u64, vec: &mut Vec<u8>) -> io::Result<usize>
+    where
+        Self: Size,
+    {
+        let hint = match self.size()? {
+            Some(size) => usize::try_from(size.saturating_sub(pos)).unwrap_or(usize::MAX),
+            None => 0,
+        };
+        read_to_end(self, pos, hint, vec)
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn read_to_end<R: ReadAt + ?Sized>(
+    io: &R,
+    pos: u64,
+    hint: usize,
+    vec: &mut Vec<u8>,
+) -> io::Result<usize> {
+    crate::vec::reserve(vec, vec.len().saturating_add(hint))?;
+    let mut total = 0;
+    loop {
+        let spare = vec.capacity() - vec.len();
+        if spare > 0 {
+            let n = io.read_to_vec_at(error::pos_after(pos, total)?, spare, vec)?;
+            total += n;
+            if n < spare {
+                return Ok(total);
+            }
+            continue;
+        }
+
+        // The vector is full, which is often because the source was exactly
+        // as long as expected. Check for more with a small buffer before
+        // growing it, which could double its size.
+        let mut probe = [0; PROBE_LEN];
+        let n = loop {
+            match io.read_at(error::pos_after(pos, total)?, &mut probe) {
+                Ok(n) => break n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        };
+        if n == 0 {
+            return Ok(total);
+        }
+        crate::vec::reserve(vec, vec.len().saturating_add(CHUNK_LEN))?;
+        vec.extend_from_slice(&probe[..n]);
+        total += n;
+    }
+}
+
+impl<R: ReadAt + ?Sized> ReadAtExt for R {}
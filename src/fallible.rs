@@ -0,0 +1,257 @@
+use core::fmt;
+
+use super::{ReadAt, Size, WriteAt, io};
+
+/// Trait for I/O objects with their own error type.
+///
+/// This is shared by [`TryReadAt`](trait.TryReadAt.html) and
+/// [`TryWriteAt`](trait.TryWriteAt.html), so an object that supports both
+/// reports the same errors from either.
+///
+/// Adapters like `Slice` sometimes detect errors themselves, for example when
+/// a position overflows, so the error type must be constructible from an
+/// `ErrorKind`.
+pub trait ErrorType {
+    /// The type of errors returned by this object.
+    type Error: fmt::Debug + From<io::ErrorKind>;
+}
+
+/// Like [`ReadAt`](trait.ReadAt.html), but returning the object's own error
+/// type instead of `io::Error`.
+///
+/// This suits backends like flash chips or remote stores, whose errors would
+/// lose information if flattened into an `io::Error`. `Slice` and `ByteIo`
+/// implement it on top of other `TryReadAt` objects, and pass errors through
+/// untouched.
+///
+/// To use an existing `ReadAt` object where a `TryReadAt` is expected, wrap it
+/// in a [`TryAdapter`](struct.TryAdapter.html). To go the other way, use an
+/// [`IoAdapter`](struct.IoAdapter.html).
+///
+/// # Examples
+///
+/// ```
+/// use positioned_io::{ErrorKind, ErrorType, Slice, TryReadAt};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum FlashError {
+///     Ecc { block: u64 },
+///     Io(ErrorKind),
+/// }
+///
+/// impl From<ErrorKind> for FlashError {
+///     fn from(kind: ErrorKind) -> Self {
+///         FlashError::Io(kind)
+///     }
+/// }
+///
+/// struct Flash;
+///
+/// impl ErrorType for Flash {
+///     type Error = FlashError;
+/// }
+///
+/// impl TryReadAt for Flash {
+///     fn try_read_at(&self, pos: u64, buf: &mut [u8]) -> Result<usize, FlashError> {
+///         Err(FlashError::Ecc { block: pos / 4096 })
+///     }
+/// }
+///
+/// let partition = Slice::new(Flash, 1 << 20, Some(1 << 16));
+/// let err = partition.try_read_at(0, &mut [0; 16]).unwrap_err();
+/// assert_eq!(err, FlashError::Ecc { block: 256 });
+/// ```
+pub trait TryReadAt: ErrorType {
+    /// Reads bytes from an offset in this source into a buffer, returning how
+    /// many bytes were read.
+    ///
+    /// See [`ReadAt::read_at()`](trait.ReadAt.html#tymethod.read_at).
+    fn try_read_at(&self, pos: u64, buf: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Reads the exact number of bytes required to fill `buf` from an offset.
+    ///
+    /// Errors with `ErrorKind::UnexpectedEof` if the "end of file" is
+    /// encountered before filling the buffer. Unlike
+    /// [`ReadAt::read_exact_at()`](trait.ReadAt.html#method.read_exact_at),
+    /// no errors are retried.
+    fn try_read_exact_at(&self, mut pos: u64, mut buf: &mut [u8]) -> Result<(), Self::Error> {
+        while !buf.is_empty() {
+            match self.try_read_at(pos, buf)? {
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                n => {
+                    let tmp = buf;
+                    buf = &mut tmp[n..];
+                    pos = pos
+                        .checked_add(n as u64)
+                        .ok_or(io::ErrorKind::InvalidInput)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Like [`WriteAt`](trait.WriteAt.html), but returning the object's own error
+/// type instead of `io::Error`.
+///
+/// See [`TryReadAt`](trait.TryReadAt.html) for details.
+pub trait TryWriteAt: ErrorType {
+    /// Writes bytes from a buffer to an offset, returning the number of bytes
+    /// written.
+    ///
+    /// See [`WriteAt::write_at()`](trait.WriteAt.html#tymethod.write_at).
+    fn try_write_at(&mut self, pos: u64, buf: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Writes a complete buffer at an offset.
+    ///
+    /// Errors with `ErrorKind::WriteZero` if a write returns `Ok(0)`. Unlike
+    /// [`WriteAt::write_all_at()`](trait.WriteAt.html#method.write_all_at),
+    /// no errors are retried.
+    fn try_write_all_at(&mut self, mut pos: u64, mut buf: &[u8]) -> Result<(), Self::Error> {
+        while !buf.is_empty() {
+            match self.try_write_at(pos, buf)? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                n => {
+                    buf = &buf[n..];
+                    pos = pos
+                        .checked_add(n as u64)
+                        .ok_or(io::ErrorKind::InvalidInput)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes this writer.
+    ///
+    /// See [`WriteAt::flush()`](trait.WriteAt.html#tymethod.flush).
+    fn try_flush(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Exposes a [`TryReadAt`](trait.TryReadAt.html) or
+/// [`TryWriteAt`](trait.TryWriteAt.html) object through `ReadAt` and
+/// `WriteAt`.
+///
+/// Errors are converted with `Into<io::Error>`. With the `std` feature, a
+/// conversion that uses
+/// [`io::Error::other()`](https://doc.rust-lang.org/std/io/struct.Error.html#method.other)
+/// keeps the original error, which can be recovered with
+/// [`io::Error::downcast()`](https://doc.rust-lang.org/std/io/struct.Error.html#method.downcast).
+#[derive(Debug, Clone)]
+pub struct IoAdapter<I> {
+    io: I,
+}
+
+impl<I> IoAdapter<I> {
+    /// Wraps an object with its own error type.
+    pub fn new(io: I) -> Self {
+        IoAdapter { io }
+    }
+
+    /// Consumes the adapter, returning the underlying value.
+    pub fn into_inner(self) -> I {
+        self.io
+    }
+
+    /// Gets a reference to the underlying value.
+    pub fn get_ref(&self) -> &I {
+        &self.io
+    }
+
+    /// Gets a mutable reference to the underlying value.
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.io
+    }
+}
+
+impl<I: TryReadAt> ReadAt for IoAdapter<I>
+where
+    I::Error: Into<io::Error>,
+{
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.io.try_read_at(pos, buf).map_err(Into::into)
+    }
+}
+
+impl<I: TryWriteAt> WriteAt for IoAdapter<I>
+where
+    I::Error: Into<io::Error>,
+{
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.io.try_write_at(pos, buf).map_err(Into::into)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.try_flush().map_err(Into::into)
+    }
+}
+
+impl<I: Size> Size for IoAdapter<I> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        self.io.size()
+    }
+}
+
+/// Exposes a `ReadAt` or `WriteAt` object through
+/// [`TryReadAt`](trait.TryReadAt.html) and
+/// [`TryWriteAt`](trait.TryWriteAt.html), with `io::Error` as the error type.
+#[derive(Debug, Clone)]
+pub struct TryAdapter<I> {
+    io: I,
+}
+
+impl<I> TryAdapter<I> {
+    /// Wraps an object that returns `io::Error`.
+    pub fn new(io: I) -> Self {
+        TryAdapter { io }
+    }
+
+    /// Consumes the adapter, returning the underlying value.
+    pub fn into_inner(self) -> I {
+        self.io
+    }
+
+    /// Gets a reference to the underlying value.
+    pub fn get_ref(&self) -> &I {
+        &self.io
+    }
+
+    /// Gets a mutable reference to the underlying value.
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.io
+    }
+}
+
+impl<I> ErrorType for TryAdapter<I> {
+    type Error = io::Error;
+}
+
+impl<I: ReadAt> TryReadAt for TryAdapter<I> {
+    fn try_read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.io.read_at(pos, buf)
+    }
+
+    fn try_read_exact_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.io.read_exact_at(pos, buf)
+    }
+}
+
+impl<I: WriteAt> TryWriteAt for TryAdapter<I> {
+    fn try_write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.io.write_at(pos, buf)
+    }
+
+    fn try_write_all_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<()> {
+        self.io.write_all_at(pos, buf)
+    }
+
+    fn try_flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl<I: Size> Size for TryAdapter<I> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        self.io.size()
+    }
+}
@@ -0,0 +1,133 @@
+use super::io;
+
+/// How an I/O object stores its data, and which operations it supports.
+///
+/// Returned by [`Geometry::geometry()`](trait.Geometry.html#tymethod.geometry).
+/// The default value assumes nothing: byte-sized blocks, no preferred I/O
+/// size, and no optional features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The smallest unit that the storage reads or writes, in bytes. Writes
+    /// of whole, aligned blocks avoid reading back the rest of a block.
+    pub logical_block_size: u64,
+    /// The unit that the storage writes internally, in bytes. This is at
+    /// least the logical block size, and writes of whole, aligned physical
+    /// blocks are usually fastest.
+    pub physical_block_size: u64,
+    /// The preferred size for efficient I/O, if there is one.
+    pub preferred_io_size: Option<u64>,
+    /// The alignment needed for direct I/O, that bypasses any caches, or
+    /// `None` if direct I/O isn't available or its requirements are unknown.
+    pub direct_io_alignment: Option<DirectIoAlignment>,
+    /// Whether writes past the end extend the object. Otherwise they are cut
+    /// short at the end, or fail.
+    pub extendable: bool,
+    /// Whether the object can have holes without storage behind them. See
+    /// [`Sparse`](trait.Sparse.html).
+    pub holes: bool,
+    /// Whether writes from several threads at once are supported, for example
+    /// through [`WriteAtShared`](trait.WriteAtShared.html).
+    pub concurrent_writes: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Capabilities {
+        Capabilities {
+            logical_block_size: 1,
+            physical_block_size: 1,
+            preferred_io_size: None,
+            direct_io_alignment: None,
+            extendable: false,
+            holes: false,
+            concurrent_writes: false,
+        }
+    }
+}
+
+impl Capabilities {
+    /// Gets the capabilities of an object seen through a window starting at
+    /// `offset`.
+    ///
+    /// Offsets in the window are only as aligned as `offset` is, so block
+    /// sizes and direct I/O alignment are reduced to match.
+    pub(crate) fn offset_by(self, offset: u64) -> Capabilities {
+        let align = |n: u64| gcd(n, offset).max(1);
+        Capabilities {
+            logical_block_size: align(self.logical_block_size),
+            physical_block_size: align(self.physical_block_size),
+            direct_io_alignment: self.direct_io_alignment.and_then(|dio| {
+                // Direct I/O needs its alignment exactly, or it fails.
+                offset
+                    .checked_rem(dio.offset)
+                    .is_none_or(|rem| rem == 0)
+                    .then_some(dio)
+            }),
+            ..self
+        }
+    }
+
+    // The capabilities of a block of memory.
+    pub(crate) fn memory(extendable: bool) -> Capabilities {
+        Capabilities {
+            extendable,
+            ..Capabilities::default()
+        }
+    }
+}
+
+/// The alignment required for direct I/O.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DirectIoAlignment {
+    /// The alignment of buffers in memory, in bytes.
+    pub memory: u64,
+    /// The alignment of offsets and lengths, in bytes.
+    pub offset: u64,
+}
+
+/// Trait to query the storage geometry and capabilities of an I/O object.
+///
+/// Generic code over `ReadAt` and `WriteAt` can use this to pick buffer
+/// sizes, align its writes, or check that an object can grow before
+/// appending to it.
+///
+/// Files on Unix get their preferred I/O size from `st_blksize`, and on Linux
+/// their direct I/O alignment from
+/// [`statx()`](http://man7.org/linux/man-pages/man2/statx.2.html). Block
+/// devices on Linux report their logical and physical sector sizes. Files on
+/// Windows report a 4096-byte block size.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use positioned_io::{Geometry, WriteAt};
+///
+/// fn append<W: WriteAt + Geometry>(io: &mut W, pos: u64, buf: &[u8]) -> io::Result<()> {
+///     if !io.geometry()?.extendable {
+///         return Err(io::Error::new(io::ErrorKind::Unsupported, "can't grow"));
+///     }
+///     io.write_all_at(pos, buf)
+/// }
+///
+/// # fn foo() -> io::Result<()> {
+/// let mut vec = Vec::new();
+/// append(&mut vec, 0, b"hello")?;
+///
+/// let mut array = [0; 4];
+/// assert!(append(&mut &mut array[..], 4, b"world").is_err());
+/// # Ok(())
+/// # }
+/// # fn main() { foo().unwrap(); }
+/// ```
+pub trait Geometry {
+    /// Gets the geometry and capabilities of this object.
+    fn geometry(&self) -> io::Result<Capabilities>;
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
@@ -0,0 +1,99 @@
+// The parts of `std::io` used by the core traits. Without the `std` feature,
+// a small replacement error type is defined here instead.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, IoSlice, IoSliceMut, Result};
+
+#[cfg(not(feature = "std"))]
+use core::{error, fmt};
+
+/// A specialized `Result` type for I/O operations.
+#[cfg(not(feature = "std"))]
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// The kind of an [`Error`](struct.Error.html).
+///
+/// These are a subset of
+/// [`std::io::ErrorKind`](https://doc.rust-lang.org/std/io/enum.ErrorKind.html),
+/// with the same meanings.
+#[cfg(not(feature = "std"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A parameter was incorrect, for example an offset that overflows.
+    InvalidInput,
+    /// Data returned by an I/O object didn't make sense.
+    InvalidData,
+    /// The operation was interrupted, and can be retried.
+    Interrupted,
+    /// The "end of file" was reached before reading all requested bytes.
+    UnexpectedEof,
+    /// A write returned `Ok(0)`, so the whole buffer couldn't be written.
+    WriteZero,
+    /// The operation isn't supported by this I/O object.
+    Unsupported,
+    /// Memory for the operation couldn't be allocated.
+    OutOfMemory,
+    /// Any other error.
+    Other,
+}
+
+#[cfg(not(feature = "std"))]
+impl ErrorKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::InvalidInput => "invalid input parameter",
+            ErrorKind::InvalidData => "invalid data",
+            ErrorKind::Interrupted => "operation interrupted",
+            ErrorKind::UnexpectedEof => "unexpected end of file",
+            ErrorKind::WriteZero => "write zero",
+            ErrorKind::Unsupported => "unsupported",
+            ErrorKind::OutOfMemory => "out of memory",
+            ErrorKind::Other => "other error",
+        }
+    }
+}
+
+/// The error type for I/O operations, when the `std` feature is disabled.
+///
+/// With the `std` feature, the traits in this crate use
+/// [`std::io::Error`](https://doc.rust-lang.org/std/io/struct.Error.html)
+/// instead, and `positioned_io::Error` is just another name for it. This
+/// replacement only carries a kind and a static message, so it works
+/// without an allocator.
+#[cfg(not(feature = "std"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Error {
+    kind: ErrorKind,
+    message: &'static str,
+}
+
+#[cfg(not(feature = "std"))]
+impl Error {
+    /// Creates an error from a kind and a message.
+    pub const fn new(kind: ErrorKind, message: &'static str) -> Error {
+        Error { kind, message }
+    }
+
+    /// Gets the kind of this error.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Error {
+        Error::new(kind, kind.as_str())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl error::Error for Error {}
@@ -115,25 +115,95 @@
 //! #     try_main().unwrap()
 //! # }
 //! ```
+//!
+//! # `no_std` support
+//!
+//! The traits, `Slice`, `ByteIo`, `ReadBuf` and the implementations for byte
+//! slices work without the standard library. Disable default features and
+//! pick what's available:
+//!
+//! * `std` (default): files, `RandomAccessFile`, `StableReader`, `Cursor`,
+//!   `copy_range()` and vectored I/O.
+//! * `alloc`: `Vec<u8>` and `Box` implementations, `AnyIo`,
+//!   `read_many_at()`, `read_to_vec_at()`, `read_cow_at()`, `read_vec_at()`
+//!   `read_to_end_at()` and `read_to_end_sized_at()`.
+//! * `byteorder` (default): `ReadBytesAtExt`, `WriteBytesAtExt` and `ByteIo`.
+//!
+//! Without `std`, the traits return this crate's own `Error` type, which only
+//! has an `ErrorKind` and a static message. With `std`, `positioned_io::Error`
+//! is simply `std::io::Error`, so no conversion is needed.
 
 #![doc(html_root_url = "https://docs.rs/positioned-io/0.3.5")]
 #![warn(missing_debug_implementations)]
 #![warn(bare_trait_objects)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
 #[cfg(feature = "byteorder")]
 extern crate byteorder;
-#[cfg(unix)]
+#[cfg(all(unix, feature = "std"))]
 extern crate libc;
 
+mod io;
+pub use crate::io::{Error, ErrorKind};
+
+mod advice;
+pub use crate::advice::Advice;
+
+#[cfg(feature = "std")]
 mod cursor;
+#[cfg(feature = "std")]
 pub use crate::cursor::{Cursor, SizeCursor};
 
 mod slice;
 pub use crate::slice::Slice;
 
+mod buf;
+pub use crate::buf::ReadBuf;
+
+mod error;
+#[cfg(feature = "error-context")]
+pub use crate::error::PositionedError;
+
+#[cfg(feature = "std")]
+mod copy;
+#[cfg(feature = "std")]
+pub use crate::copy::copy_range;
+
+mod fallible;
+pub use crate::fallible::{ErrorType, IoAdapter, TryAdapter, TryReadAt, TryWriteAt};
+
+mod ext;
+pub use crate::ext::ReadAtExt;
+
+mod geometry;
+pub use crate::geometry::{Capabilities, DirectIoAlignment, Geometry};
+
+#[cfg(feature = "std")]
+mod stable;
+#[cfg(feature = "std")]
+pub use crate::stable::{ConcurrentModification, StableReader};
+
+#[cfg(feature = "alloc")]
+mod any;
+#[cfg(feature = "alloc")]
+pub use crate::any::AnyIo;
+
+mod sparse;
+#[cfg(feature = "std")]
+pub use crate::sparse::copy_sparse;
+pub use crate::sparse::{Extent, ExtentKind, Extents, Sparse};
+
 #[cfg(feature = "byteorder")]
 mod byteio;
-use std::{fs::File, io};
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, vec::Vec};
+#[cfg(feature = "std")]
+use std::fs::File;
+
+#[cfg(feature = "std")]
+use crate::io::{IoSlice, IoSliceMut};
 
 #[cfg(feature = "byteorder")]
 pub use crate::byteio::{ByteIo, ReadBytesAtExt, WriteBytesAtExt};
@@ -188,27 +258,273 @@ pub trait ReadAt {
     /// See [`Read::read_exact()`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_exact)
     /// for details.
     fn read_exact_at(&self, mut pos: u64, mut buf: &mut [u8]) -> io::Result<()> {
+        let (start, len) = (pos, buf.len() as u64);
+        let context = |e, pos| error::context(e, "ReadAt::read_exact_at", start, len, pos - start);
         while !buf.is_empty() {
             match self.read_at(pos, buf) {
                 Ok(0) => break,
                 Ok(n) => {
                     let tmp = buf;
                     buf = &mut tmp[n..];
-                    pos += n as u64;
+                    pos = error::pos_after(pos, n).map_err(|e| context(e, pos))?;
                 }
                 Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
-                Err(e) => return Err(e),
+                Err(e) => return Err(context(e, pos)),
             }
         }
         if !buf.is_empty() {
-            Err(io::Error::new(
-                io::ErrorKind::UnexpectedEof,
-                "failed to fill whole buffer",
+            Err(context(
+                io::Error::new(io::ErrorKind::UnexpectedEof, "failed to fill whole buffer"),
+                pos,
             ))
         } else {
             Ok(())
         }
     }
+
+    /// Like `read_at()`, except that it reads into a slice of buffers.
+    ///
+    /// Data is copied to fill each buffer in order, with the final buffer
+    /// written to possibly being only partially filled. The default
+    /// implementation reads into the first non-empty buffer.
+    ///
+    /// See [`Read::read_vectored()`](https://doc.rust-lang.org/std/io/trait.Read.html#method.read_vectored)
+    /// for details.
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let buf = bufs
+            .iter_mut()
+            .find(|b| !b.is_empty())
+            .map_or(&mut [][..], |b| &mut **b);
+        self.read_at(pos, buf)
+    }
+
+    /// Reads many independent ranges, returning a result for each request.
+    ///
+    /// Each request is an offset together with the buffer to read into, and
+    /// the result at the same index reports how many bytes were read into
+    /// that buffer, just like `read_at()`. Requests may be given in any order.
+    ///
+    /// The default implementation simply calls `read_at()` for each request.
+    /// Implementations may reorder or combine requests, for example by merging
+    /// ranges that are adjacent in a file into a single system call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use positioned_io::ReadAt;
+    ///
+    /// # fn foo() -> io::Result<()> {
+    /// let data = vec![0, 1, 2, 3, 4, 5, 6, 7];
+    /// let mut a = [0; 2];
+    /// let mut b = [0; 4];
+    /// let results = data.read_many_at(&mut [(6, &mut a[..]), (1, &mut b[..])]);
+    /// assert_eq!(results[0].as_ref().ok(), Some(&2));
+    /// assert_eq!(results[1].as_ref().ok(), Some(&4));
+    /// assert_eq!(a, [6, 7]);
+    /// assert_eq!(b, [1, 2, 3, 4]);
+    /// # Ok(())
+    /// # }
+    /// # fn main() { foo().unwrap(); }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        requests
+            .iter_mut()
+            .map(|(pos, buf)| self.read_at(*pos, buf))
+            .collect()
+    }
+
+    /// Like `read_at()`, except that it reads into a buffer that may not be
+    /// initialized yet.
+    ///
+    /// Bytes are appended to the filled part of `buf`, and the number of bytes
+    /// read is returned. This avoids having to zero large buffers that are
+    /// about to be overwritten anyway.
+    ///
+    /// The default implementation zeroes the rest of the buffer and calls
+    /// `read_at()`. Files, vectors, byte slices and `Slice`s read into the
+    /// buffer directly.
+    ///
+    /// Implementations must fill the buffer they're given, rather than
+    /// replacing it with another `ReadBuf`. Callers such as
+    /// `read_to_vec_at()` fail with `ErrorKind::InvalidData` if it's replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use std::mem::MaybeUninit;
+    /// use positioned_io::{ReadAt, ReadBuf};
+    ///
+    /// # fn foo() -> io::Result<()> {
+    /// let data = vec![0, 1, 2, 3, 4, 5, 6, 7];
+    /// let mut storage = [MaybeUninit::uninit(); 4];
+    /// let mut buf = ReadBuf::new(&mut storage);
+    /// data.read_buf_at(2, &mut buf)?;
+    /// assert_eq!(buf.filled(), &[2, 3, 4, 5]);
+    /// # Ok(())
+    /// # }
+    /// # fn main() { foo().unwrap(); }
+    /// ```
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        let n = self.read_at(pos, buf.initialize_unfilled())?;
+        buf.advance(n);
+        Ok(n)
+    }
+
+    /// Reads up to `len` bytes from an offset, appending them to a vector.
+    ///
+    /// The bytes are read straight into the spare capacity of `vec`, without
+    /// initializing it first. Reading stops early only at the "end of file",
+    /// and the number of bytes appended is returned. If an error occurs, any
+    /// bytes read so far are still appended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// use positioned_io::ReadAt;
+    ///
+    /// # fn foo() -> io::Result<()> {
+    /// let data: &[u8] = &[0, 1, 2, 3, 4, 5, 6, 7];
+    /// let mut vec = vec![9];
+    /// assert_eq!(data.read_to_vec_at(6, 4, &mut vec)?, 2);
+    /// assert_eq!(vec, [9, 6, 7]);
+    /// # Ok(())
+    /// # }
+    /// # fn main() { foo().unwrap(); }
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn read_to_vec_at(&self, pos: u64, len: usize, vec: &mut Vec<u8>) -> io::Result<usize> {
+        vec.reserve(len);
+        let start = vec.len();
+        let mut buf = ReadBuf::new(&mut vec.spare_capacity_mut()[..len]);
+        let memory = buf.memory();
+        let mut total = 0;
+        let mut result = Ok(());
+        while buf.remaining() > 0 {
+            let read = match error::pos_after(pos, total) {
+                Ok(pos) => self.read_buf_at(pos, &mut buf),
+                Err(e) => Err(e),
+            };
+            // Only trust the count if the buffer is still our memory.
+            if let Err(e) = buf.check_memory(memory) {
+                result = Err(e);
+                break;
+            }
+            total = buf.len();
+            match read {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        // Safety: the buffer was the vector's spare capacity when it had this
+        // many bytes filled, and ReadBuf guarantees that they're initialized.
+        unsafe { vec.set_len(start + total) };
+        result.map(|_| total)
+    }
+
+    /// Reads up to `len` bytes from an offset, borrowing them if they're
+    /// already in memory.
+    ///
+    /// Like [`ReadAtRef::read_ref_at()`](trait.ReadAtRef.html#tymethod.read_ref_at),
+    /// but for any source. Fewer bytes are returned only at the "end of
+    /// file". The default implementation copies the bytes into a new vector
+    /// with `read_to_vec_at()`, while in-memory sources return a borrowed
+    /// slice.
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        let mut vec = Vec::new();
+        self.read_to_vec_at(pos, len, &mut vec)?;
+        Ok(Cow::Owned(vec))
+    }
+
+    /// Returns the `File` that this reads from, if offsets in this source are
+    /// the same as offsets in the file.
+    ///
+    /// This allows functions like [`copy_range()`](fn.copy_range.html) to hand
+    /// work off to the operating system. The default implementation returns
+    /// `None`.
+    #[cfg(feature = "std")]
+    fn as_read_file(&self) -> Option<&File> {
+        None
+    }
+
+    /// Hints how `len` bytes at an offset will be read, so the source can
+    /// prefetch them or drop them from its cache.
+    ///
+    /// A `len` of zero covers everything from `pos` onwards. Advice never
+    /// changes what is read, and sources may ignore it. The default
+    /// implementation does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// use positioned_io::{Advice, RandomAccessFile, ReadAt};
+    ///
+    /// # fn foo() -> io::Result<()> {
+    /// let file = RandomAccessFile::open("big.log")?;
+    /// let mut buf = vec![0; 1 << 20];
+    /// let mut pos = 0;
+    /// file.advise(0, 0, Advice::Sequential)?;
+    /// loop {
+    ///     let n = file.read_at(pos, &mut buf)?;
+    ///     if n == 0 {
+    ///         break;
+    ///     }
+    ///     // Done with this chunk, so don't keep it cached.
+    ///     file.advise(pos, n as u64, Advice::DontNeed)?;
+    ///     pos += n as u64;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        let _ = (pos, len, advice);
+        Ok(())
+    }
+}
+
+/// Trait for sources that can lend out their bytes without copying.
+///
+/// This is implemented for in-memory sources, like byte slices and vectors,
+/// and is a good fit for memory-mapped files. Parsers can then borrow headers
+/// and records straight from the source.
+///
+/// For code that should also work with files, use
+/// [`ReadAt::read_cow_at()`](trait.ReadAt.html#method.read_cow_at) instead,
+/// which only copies when it has to.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use positioned_io::{ReadAtRef, Slice};
+///
+/// # fn foo() -> io::Result<()> {
+/// let data = vec![0, 1, 2, 3, 4, 5, 6, 7];
+/// let slice = Slice::new(&data, 4, None);
+/// assert_eq!(slice.read_ref_at(1, 2)?, &[5, 6]);
+/// // Fewer bytes at the end.
+/// assert_eq!(slice.read_ref_at(2, 10)?, &[6, 7]);
+/// # Ok(())
+/// # }
+/// # fn main() { foo().unwrap(); }
+/// ```
+pub trait ReadAtRef: ReadAt {
+    /// Borrows up to `len` bytes at an offset.
+    ///
+    /// Fewer bytes are returned only if the "end of file" is reached, so an
+    /// empty slice means the offset is at or past the end.
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]>;
 }
 
 /// Trait for writing bytes at an offset.
@@ -257,25 +573,55 @@ pub trait WriteAt {
     /// See [`Write::write_all()`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all)
     /// for details.
     fn write_all_at(&mut self, mut pos: u64, mut buf: &[u8]) -> io::Result<()> {
+        let (start, len) = (pos, buf.len() as u64);
+        let context = |e, pos| error::context(e, "WriteAt::write_all_at", start, len, pos - start);
         while !buf.is_empty() {
             match self.write_at(pos, buf) {
                 Ok(0) => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::WriteZero,
-                        "failed to write whole buffer",
-                    ))
+                    return Err(context(
+                        io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer"),
+                        pos,
+                    ));
                 }
                 Ok(n) => {
                     buf = &buf[n..];
-                    pos += n as u64;
+                    pos = error::pos_after(pos, n).map_err(|e| context(e, pos))?;
                 }
                 Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
-                Err(e) => return Err(e),
+                Err(e) => return Err(context(e, pos)),
             }
         }
         Ok(())
     }
 
+    /// Like `write_at()`, except that it writes from a slice of buffers.
+    ///
+    /// Data is copied from each buffer in order, with the final buffer read
+    /// from possibly being only partially consumed. The default
+    /// implementation writes the first non-empty buffer.
+    ///
+    /// See [`Write::write_vectored()`](https://doc.rust-lang.org/std/io/trait.Write.html#method.write_vectored)
+    /// for details.
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let buf = bufs
+            .iter()
+            .find(|b| !b.is_empty())
+            .map_or(&[][..], |b| &**b);
+        self.write_at(pos, buf)
+    }
+
+    /// Returns the `File` that this writes to, if offsets in this writer are
+    /// the same as offsets in the file.
+    ///
+    /// This allows functions like [`copy_range()`](fn.copy_range.html) to hand
+    /// work off to the operating system. The default implementation returns
+    /// `None`.
+    #[cfg(feature = "std")]
+    fn as_write_file(&self) -> Option<&File> {
+        None
+    }
+
     /// Flush this writer, ensuring that any intermediately buffered data
     /// reaches its destination.
     ///
@@ -291,6 +637,93 @@ pub trait WriteAt {
     fn flush(&mut self) -> io::Result<()>;
 }
 
+/// Trait for writing bytes at an offset through a shared reference.
+///
+/// Some objects can be written without exclusive access, like files, whose
+/// positioned writes don't share any state. Implementing this instead of
+/// `WriteAt` lets many threads write through one shared object, without a
+/// lock around the whole thing. Types that need exclusive access can still
+/// be shared by putting them in a `Mutex` or `RwLock`, which implement this
+/// trait by locking for each write.
+///
+/// On Windows, `File` doesn't implement this, since its positioned writes
+/// move the file cursor, which `WriteAt` restores afterwards. Use a
+/// `RandomAccessFile` to write a file through a shared reference.
+///
+/// A shared reference to any `WriteAtShared` is also a `WriteAt`, so it can
+/// be used with `Slice`, `Cursor` or anything else that wants a `WriteAt`.
+///
+/// Since the method names are the same as `WriteAt`, types that implement
+/// both may need to be called as `WriteAtShared::write_at(&file, ...)`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// use std::{sync::Arc, thread};
+/// use positioned_io::{RandomAccessFile, Slice, WriteAt};
+///
+/// # fn foo() -> io::Result<()> {
+/// let file = Arc::new(RandomAccessFile::try_new(std::fs::File::create("out.bin")?)?);
+///
+/// let workers: Vec<_> = (0..4)
+///     .map(|i| {
+///         let file = Arc::clone(&file);
+///         thread::spawn(move || {
+///             // Each worker writes its own 1 MiB chunk.
+///             let mut chunk = Slice::new(file, i << 20, Some(1 << 20));
+///             chunk.write_all_at(0, &[i as u8; 1 << 20])
+///         })
+///     })
+///     .collect();
+/// for worker in workers {
+///     worker.join().unwrap()?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait WriteAtShared {
+    /// Writes bytes from a buffer to an offset, returning the number of bytes
+    /// written.
+    ///
+    /// See [`WriteAt::write_at()`](trait.WriteAt.html#tymethod.write_at).
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize>;
+
+    /// Writes a complete buffer at an offset.
+    ///
+    /// See [`WriteAt::write_all_at()`](trait.WriteAt.html#method.write_all_at).
+    fn write_all_at(&self, pos: u64, buf: &[u8]) -> io::Result<()> {
+        WriteAt::write_all_at(&mut &*self, pos, buf)
+    }
+
+    /// Like `write_at()`, except that it writes from a slice of buffers.
+    ///
+    /// See [`WriteAt::write_vectored_at()`](trait.WriteAt.html#method.write_vectored_at).
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let buf = bufs
+            .iter()
+            .find(|b| !b.is_empty())
+            .map_or(&[][..], |b| &**b);
+        self.write_at(pos, buf)
+    }
+
+    /// Returns the `File` that this writes to, if offsets in this writer are
+    /// the same as offsets in the file.
+    ///
+    /// See [`WriteAt::as_write_file()`](trait.WriteAt.html#method.as_write_file).
+    #[cfg(feature = "std")]
+    fn as_write_file(&self) -> Option<&File> {
+        None
+    }
+
+    /// Flush this writer, ensuring that any intermediately buffered data
+    /// reaches its destination.
+    ///
+    /// See [`WriteAt::flush()`](trait.WriteAt.html#tymethod.flush).
+    fn flush(&self) -> io::Result<()>;
+}
+
 /// Trait to get the size in bytes of an I/O object.
 ///
 /// Implementing this for a types with `ReadAt` or `WriteAt` makes it easier
@@ -330,6 +763,7 @@ pub trait Size {
     fn size(&self) -> io::Result<Option<u64>>;
 }
 
+#[cfg(feature = "std")]
 impl Size for File {
     fn size(&self) -> io::Result<Option<u64>> {
         let md = self.metadata()?;
@@ -341,30 +775,185 @@ impl Size for File {
     }
 }
 
+/// Trait to truncate or extend an I/O object.
+///
+/// This complements [`Size`](trait.Size.html), so that generic code can also
+/// shrink or grow its storage. When extending, the new bytes are filled with
+/// the value 0.
+///
+/// Note that `Vec` has an unrelated inherent `set_len()` method, so call
+/// `SetLen::set_len(&mut vec, len)` when using a vector directly.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use positioned_io::{SetLen, Size, WriteAt};
+///
+/// // Append a record, then drop it again.
+/// fn append_and_undo<W: WriteAt + Size + SetLen>(io: &mut W) -> io::Result<()> {
+///     let end = io.size()?.unwrap_or(0);
+///     io.write_all_at(end, b"record")?;
+///     io.set_len(end)
+/// }
+///
+/// # fn foo() -> io::Result<()> {
+/// let mut v = vec![1, 2, 3];
+/// append_and_undo(&mut v)?;
+/// assert_eq!(v, vec![1, 2, 3]);
+/// # Ok(())
+/// # }
+/// # fn main() { foo().unwrap(); }
+/// ```
+pub trait SetLen {
+    /// Truncates or extends this object so that its size becomes `len` bytes.
+    ///
+    /// See [`File::set_len()`](https://doc.rust-lang.org/std/fs/struct.File.html#method.set_len)
+    /// for details.
+    fn set_len(&mut self, len: u64) -> io::Result<()>;
+}
+
+#[cfg(feature = "std")]
+impl SetLen for File {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        File::set_len(self, len)
+    }
+}
+
+/// Trait to control how storage is allocated for ranges of an I/O object.
+///
+/// This exposes the semantics of Linux
+/// [`fallocate()`](http://man7.org/linux/man-pages/man2/fallocate.2.html),
+/// which is useful for building sparse files. Files on other platforms, or on
+/// file systems that don't support an operation, return an error of kind
+/// [`Unsupported`](https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.Unsupported).
+/// In-memory objects emulate these operations by writing zeros.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use positioned_io::Allocate;
+///
+/// # fn foo() -> io::Result<()> {
+/// let mut v = vec![1; 8];
+/// v.punch_hole(2, 4)?;
+/// assert_eq!(v, vec![1, 1, 0, 0, 0, 0, 1, 1]);
+/// v.preallocate(4, 8)?;
+/// assert_eq!(v.len(), 12);
+/// # Ok(())
+/// # }
+/// # fn main() { foo().unwrap(); }
+/// ```
+pub trait Allocate {
+    /// Allocates storage for `len` bytes at an offset, so that later writes to
+    /// that range won't fail for lack of space.
+    ///
+    /// The object is extended if the range reaches past its end. Existing
+    /// data is left untouched.
+    fn preallocate(&mut self, pos: u64, len: u64) -> io::Result<()>;
+
+    /// Deallocates the storage for `len` bytes at an offset.
+    ///
+    /// Afterwards the range reads as zeros. The size of the object never
+    /// changes.
+    fn punch_hole(&mut self, pos: u64, len: u64) -> io::Result<()>;
+
+    /// Sets `len` bytes at an offset to zero, preferably without writing them
+    /// out.
+    ///
+    /// The object is extended if the range reaches past its end.
+    fn zero_range(&mut self, pos: u64, len: u64) -> io::Result<()>;
+}
+
+/// Trait to make sure that written data has reached durable storage.
+///
+/// [`WriteAt::flush()`](trait.WriteAt.html#method.flush) only pushes data out
+/// of userspace buffers. This trait exposes the stronger guarantees of
+/// [`File::sync_data()`](https://doc.rust-lang.org/std/fs/struct.File.html#method.sync_data)
+/// and [`File::sync_all()`](https://doc.rust-lang.org/std/fs/struct.File.html#method.sync_all),
+/// so they remain available once a file is wrapped in a `Slice`, `ByteIo` or
+/// `Box`. In-memory objects have nothing to sync, so these are no-ops.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// use std::fs::File;
+/// use positioned_io::{Durable, Slice, WriteAt};
+///
+/// # fn foo() -> io::Result<()> {
+/// let file = File::create("log.bin")?;
+/// let mut slice = Slice::new(file, 4096, Some(4096));
+/// slice.write_all_at(0, b"header")?;
+/// slice.sync_range(0, 6)?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait Durable {
+    /// Syncs written data to storage, without necessarily syncing metadata
+    /// that isn't needed to read it back.
+    fn sync_data(&self) -> io::Result<()>;
+
+    /// Syncs written data and all metadata to storage.
+    fn sync_all(&self) -> io::Result<()>;
+
+    /// Syncs written data in `len` bytes at an offset to storage.
+    ///
+    /// Files on Linux use
+    /// [`sync_file_range()`](http://man7.org/linux/man-pages/man2/sync_file_range.2.html),
+    /// which waits for the range to be written out but doesn't flush metadata
+    /// or disk caches, so it's cheaper than `sync_data()` but also weaker. The
+    /// default implementation syncs everything with `sync_data()`.
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        let _ = (pos, len);
+        self.sync_data()
+    }
+}
+
 // Implementation for Unix files.
-#[cfg(unix)]
+#[cfg(all(unix, feature = "std"))]
 mod unix;
 
 // Implementation for Windows files.
-#[cfg(windows)]
+#[cfg(all(windows, feature = "std"))]
 mod windows;
 
 // RandomAccess file wrapper.
-#[cfg(any(windows, unix))]
+#[cfg(all(any(windows, unix), feature = "std"))]
 mod raf;
-#[cfg(any(windows, unix))]
-pub use crate::raf::RandomAccessFile;
+#[cfg(all(any(windows, unix), feature = "std"))]
+pub use crate::raf::{RandomAccessFile, RandomAccessFileOptions};
+
+// Byte-range locks for RandomAccessFile.
+#[cfg(all(target_os = "linux", feature = "std"))]
+mod lock;
+#[cfg(all(target_os = "linux", feature = "std"))]
+pub use crate::lock::RangeLock;
+
+// io_uring file wrapper.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod uring;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub use crate::uring::UringFile;
 
 // Implementation for arrays, vectors.
 mod array;
 mod refs;
+#[cfg(feature = "alloc")]
 mod vec;
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    struct _AssertObjectSafe1(Box<dyn ReadAt>);
-    struct _AssertObjectSafe2(Box<dyn WriteAt>);
-    struct _AssertObjectSafe3(Box<dyn Size>);
+    struct _AssertObjectSafe1(&'static dyn ReadAt);
+    struct _AssertObjectSafe2(&'static dyn WriteAt);
+    struct _AssertObjectSafe3(&'static dyn Size);
+    struct _AssertObjectSafe4(&'static dyn SetLen);
+    struct _AssertObjectSafe5(&'static dyn Allocate);
+    struct _AssertObjectSafe6(&'static dyn Durable);
+    struct _AssertObjectSafe7(&'static dyn ReadAtRef);
+    struct _AssertObjectSafe8(&'static dyn WriteAtShared);
+    struct _AssertObjectSafe9(&'static dyn Geometry);
 }
@@ -0,0 +1,230 @@
+use std::{fs::File, io, marker::PhantomData, os::unix::io::AsRawFd};
+
+use super::{RandomAccessFile, Size, Slice};
+
+/// A lock on a range of a [`RandomAccessFile`](struct.RandomAccessFile.html),
+/// which is released when dropped.
+///
+/// Locks are Linux
+/// [open file description locks](https://www.gnu.org/software/libc/manual/html_node/Open-File-Description-Locks.html).
+/// They are advisory, so they only keep out other processes or files that
+/// also lock. They belong to the open file rather than the process, so two
+/// `RandomAccessFile`s opened separately conflict with each other even in the
+/// same process.
+///
+/// Each lock opens the file again through `/proc/self/fd`, and holds the
+/// range through that new open file description. So every guard is
+/// independent, even on the same `RandomAccessFile` or its
+/// [`try_clone()`](struct.RandomAccessFile.html#method.try_clone)s:
+/// overlapping guards conflict just like locks held by other processes, and
+/// dropping one never releases another. Like with any lock, waiting for a
+/// range that conflicts with a guard held by the same thread never finishes.
+/// This needs `/proc` to be mounted.
+///
+/// Only available on Linux.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// use positioned_io::{RandomAccessFile, WriteAt};
+///
+/// # fn foo() -> io::Result<()> {
+/// let file = RandomAccessFile::try_new(
+///     std::fs::OpenOptions::new().write(true).open("shared.db")?,
+/// )?;
+///
+/// // Keep other processes out while updating a record.
+/// let lock = file.lock_exclusive(4096, 512)?;
+/// (&file).write_all_at(4096, &[0; 512])?;
+/// drop(lock);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use = "the range is unlocked as soon as the lock is dropped"]
+pub struct RangeLock<'a> {
+    // Closing this releases the lock.
+    file: File,
+    pos: u64,
+    len: u64,
+    _file: PhantomData<&'a File>,
+}
+
+impl RangeLock<'_> {
+    /// Gets the offset where the locked range starts.
+    pub fn pos(&self) -> u64 {
+        self.pos
+    }
+
+    /// Gets the offset where the locked range ends, or `None` if it extends
+    /// forever.
+    pub fn end(&self) -> Option<u64> {
+        match self.len {
+            0 => None,
+            len => Some(self.pos + len),
+        }
+    }
+
+    /// Releases the lock, reporting any error.
+    ///
+    /// Dropping the lock does the same, but ignores errors.
+    pub fn unlock(self) -> io::Result<()> {
+        set_lock(&self.file, libc::F_UNLCK, self.pos, self.len, false)
+    }
+}
+
+/// Byte-range locks.
+///
+/// A `len` of zero locks everything from `pos` onwards, including any bytes
+/// later written past the current end of the file.
+impl RandomAccessFile {
+    /// Locks a range for shared access, waiting until no other guard or
+    /// process holds an exclusive lock that overlaps it.
+    ///
+    /// The file must be open for reading.
+    pub fn lock_shared(&self, pos: u64, len: u64) -> io::Result<RangeLock<'_>> {
+        lock(&self.file, libc::F_RDLCK, pos, len)
+    }
+
+    /// Locks a range for exclusive access, waiting until no other guard or
+    /// process holds a lock that overlaps it.
+    ///
+    /// The file must be open for writing.
+    pub fn lock_exclusive(&self, pos: u64, len: u64) -> io::Result<RangeLock<'_>> {
+        lock(&self.file, libc::F_WRLCK, pos, len)
+    }
+
+    /// Tries to lock a range for shared access, returning `Ok(None)` if it's
+    /// already locked exclusively.
+    pub fn try_lock_shared(&self, pos: u64, len: u64) -> io::Result<Option<RangeLock<'_>>> {
+        try_lock(&self.file, libc::F_RDLCK, pos, len)
+    }
+
+    /// Tries to lock a range for exclusive access, returning `Ok(None)` if
+    /// it's already locked.
+    pub fn try_lock_exclusive(&self, pos: u64, len: u64) -> io::Result<Option<RangeLock<'_>>> {
+        try_lock(&self.file, libc::F_WRLCK, pos, len)
+    }
+}
+
+/// Byte-range locks, in the coordinates of the slice.
+///
+/// Ranges are clamped to the slice. A `len` of zero locks the rest of the
+/// slice, or everything from `pos` onwards if the slice has no size.
+impl<'a> Slice<&'a RandomAccessFile> {
+    /// Locks a range of the slice for shared access, waiting if needed.
+    ///
+    /// See [`RandomAccessFile::lock_shared()`](struct.RandomAccessFile.html#method.lock_shared).
+    pub fn lock_shared(&self, pos: u64, len: u64) -> io::Result<RangeLock<'a>> {
+        let (pos, len) = self.lock_range(pos, len)?;
+        lock(&self.get_ref().file, libc::F_RDLCK, pos, len)
+    }
+
+    /// Locks a range of the slice for exclusive access, waiting if needed.
+    ///
+    /// See [`RandomAccessFile::lock_exclusive()`](struct.RandomAccessFile.html#method.lock_exclusive).
+    pub fn lock_exclusive(&self, pos: u64, len: u64) -> io::Result<RangeLock<'a>> {
+        let (pos, len) = self.lock_range(pos, len)?;
+        lock(&self.get_ref().file, libc::F_WRLCK, pos, len)
+    }
+
+    /// Tries to lock a range of the slice for shared access.
+    ///
+    /// See [`RandomAccessFile::try_lock_shared()`](struct.RandomAccessFile.html#method.try_lock_shared).
+    pub fn try_lock_shared(&self, pos: u64, len: u64) -> io::Result<Option<RangeLock<'a>>> {
+        let (pos, len) = self.lock_range(pos, len)?;
+        try_lock(&self.get_ref().file, libc::F_RDLCK, pos, len)
+    }
+
+    /// Tries to lock a range of the slice for exclusive access.
+    ///
+    /// See [`RandomAccessFile::try_lock_exclusive()`](struct.RandomAccessFile.html#method.try_lock_exclusive).
+    pub fn try_lock_exclusive(&self, pos: u64, len: u64) -> io::Result<Option<RangeLock<'a>>> {
+        let (pos, len) = self.lock_range(pos, len)?;
+        try_lock(&self.get_ref().file, libc::F_WRLCK, pos, len)
+    }
+
+    /// Translates a range to the underlying file.
+    fn lock_range(&self, pos: u64, len: u64) -> io::Result<(u64, u64)> {
+        let size = self.size()?;
+        let len = match (len, size) {
+            (0, None) => 0,
+            (0, Some(_)) => self.avail_len(pos, u64::MAX),
+            _ => self.avail_len(pos, len),
+        };
+        if len == 0 && size.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "lock range outside of slice",
+            ));
+        }
+        Ok((self.inner_pos(pos)?, len))
+    }
+}
+
+fn lock(file: &File, kind: libc::c_int, pos: u64, len: u64) -> io::Result<RangeLock<'_>> {
+    let file = crate::unix::reopen(file)?;
+    loop {
+        match set_lock(&file, kind, pos, len, true) {
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+            Ok(()) => {
+                return Ok(RangeLock {
+                    file,
+                    pos,
+                    len,
+                    _file: PhantomData,
+                });
+            }
+        }
+    }
+}
+
+fn try_lock(
+    file: &File,
+    kind: libc::c_int,
+    pos: u64,
+    len: u64,
+) -> io::Result<Option<RangeLock<'_>>> {
+    let file = crate::unix::reopen(file)?;
+    match set_lock(&file, kind, pos, len, false) {
+        Ok(()) => Ok(Some(RangeLock {
+            file,
+            pos,
+            len,
+            _file: PhantomData,
+        })),
+        Err(ref e) if matches!(e.raw_os_error(), Some(libc::EAGAIN) | Some(libc::EACCES)) => {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn set_lock(file: &File, kind: libc::c_int, pos: u64, len: u64, wait: bool) -> io::Result<()> {
+    let offset = |n: u64| {
+        libc::off64_t::try_from(n)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset too large"))
+    };
+    // OFD locks always take the 64-bit structure, even on 32-bit systems.
+    let mut flock: libc::flock64 = unsafe { std::mem::zeroed() };
+    flock.l_type = kind as libc::c_short;
+    flock.l_whence = libc::SEEK_SET as libc::c_short;
+    flock.l_start = offset(pos)?;
+    flock.l_len = offset(len)?;
+    // Must be zero for OFD locks.
+    flock.l_pid = 0;
+
+    let cmd = if wait {
+        libc::F_OFD_SETLKW
+    } else {
+        libc::F_OFD_SETLK
+    };
+    let ret = unsafe { libc::fcntl(file.as_raw_fd(), cmd, &mut flock) };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
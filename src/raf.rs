@@ -1,20 +1,43 @@
 #[cfg(windows)]
 use std::io::{Seek, SeekFrom};
 #[cfg(unix)]
-use std::os::unix::fs::FileExt;
+use std::os::unix::{
+    fs::FileExt,
+    io::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+};
 #[cfg(windows)]
-use std::os::windows::fs::FileExt;
-use std::{fs::File, io, io::Write, path::Path, sync::Arc};
+use std::os::windows::{
+    fs::FileExt,
+    io::{AsHandle, AsRawHandle, BorrowedHandle, OwnedHandle, RawHandle},
+};
+use std::{
+    fs::{File, Metadata, OpenOptions},
+    io,
+    io::{IoSlice, IoSliceMut, Write},
+    path::Path,
+};
 
-use super::{ReadAt, Size, WriteAt};
+use super::{
+    Advice, Allocate, Capabilities, Durable, Geometry, ReadAt, ReadBuf, SetLen, Size, Sparse,
+    WriteAt, WriteAtShared,
+};
 
 /// A wrapper for `File` that provides optimized random access through
 /// `ReadAt` and `WriteAt`.
 ///
-/// * On Unix the operating system is advised that reads will be in random
-///   order (`FADV_RANDOM`).
+/// * On Linux the operating system is advised that reads will be in random
+///   order (`FADV_RANDOM`). Use [`advise()`](trait.ReadAt.html#method.advise)
+///   to change this, for the whole file or just part of it, or
+///   [`options()`](#method.options) to pick another pattern when opening.
 /// * On Windows the implementation is orders of magnitude faster than `ReadAt`
 ///   directly on `File`.
+/// * On Linux, a file opened for appending can be read, but writes fail with
+///   `ErrorKind::InvalidInput`, since the kernel would ignore their offset.
+///   This is checked before every write, so it also applies if `O_APPEND` is
+///   set later.
+/// * The underlying file can be borrowed with `AsFd` on Unix or `AsHandle` on
+///   Windows, and converted to and from an owned descriptor or handle. An
+///   `Arc<RandomAccessFile>` can be read and written from many threads.
 ///
 /// # Examples
 ///
@@ -41,7 +64,7 @@ use super::{ReadAt, Size, WriteAt};
 /// # }
 #[derive(Debug)]
 pub struct RandomAccessFile {
-    file: File,
+    pub(crate) file: File,
     #[cfg(not(unix))]
     pos: u64,
 }
@@ -55,20 +78,25 @@ impl RandomAccessFile {
 
     /// Creates a `RandomAccessFile` wrapper around a `File`.
     pub fn try_new(file: File) -> io::Result<RandomAccessFile> {
-        RandomAccessFile::try_new_impl(file)
+        RandomAccessFile::with_advice(file, Some(Advice::Random))
     }
 
-    #[cfg(all(unix, target_os = "linux"))]
-    fn try_new_impl(file: File) -> io::Result<RandomAccessFile> {
-        unsafe {
-            use std::os::unix::io::AsRawFd;
-            libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_RANDOM);
-        }
+    /// Creates a blank set of options for opening a `RandomAccessFile`.
+    ///
+    /// See [`RandomAccessFileOptions`](struct.RandomAccessFileOptions.html).
+    pub fn options() -> RandomAccessFileOptions {
+        RandomAccessFileOptions::new()
+    }
 
-        Ok(RandomAccessFile { file })
+    fn with_advice(file: File, advice: Option<Advice>) -> io::Result<RandomAccessFile> {
+        if let Some(advice) = advice {
+            // This is only a hint, so failures don't matter.
+            let _ = ReadAt::advise(&file, 0, 0, advice);
+        }
+        RandomAccessFile::try_new_impl(file)
     }
 
-    #[cfg(all(unix, not(target_os = "linux")))]
+    #[cfg(unix)]
     fn try_new_impl(file: File) -> io::Result<RandomAccessFile> {
         Ok(RandomAccessFile { file })
     }
@@ -79,6 +107,22 @@ impl RandomAccessFile {
         Ok(RandomAccessFile { file, pos })
     }
 
+    /// Creates a new `RandomAccessFile` that shares the same underlying file.
+    ///
+    /// See [`File::try_clone()`](https://doc.rust-lang.org/std/fs/struct.File.html#method.try_clone).
+    pub fn try_clone(&self) -> io::Result<RandomAccessFile> {
+        Ok(RandomAccessFile {
+            file: self.file.try_clone()?,
+            #[cfg(not(unix))]
+            pos: self.pos,
+        })
+    }
+
+    /// Queries metadata about the underlying file.
+    pub fn metadata(&self) -> io::Result<Metadata> {
+        self.file.metadata()
+    }
+
     /// Tries to unwrap the inner `File`.
     pub fn try_into_inner(self) -> Result<File, (RandomAccessFile, io::Error)> {
         RandomAccessFile::try_into_inner_impl(self)
@@ -98,21 +142,302 @@ impl RandomAccessFile {
     }
 }
 
+/// Options for opening a [`RandomAccessFile`](struct.RandomAccessFile.html).
+///
+/// This works like
+/// [`std::fs::OpenOptions`](https://doc.rust-lang.org/std/fs/struct.OpenOptions.html),
+/// with extra settings for random access. Files can't be opened for
+/// appending, since that would break writes at an offset.
+///
+/// `noatime()` and `direct()` are only supported on Linux and Android, and
+/// `dsync()` on Unix. Elsewhere, asking for them makes
+/// [`open()`](#method.open) fail with `ErrorKind::Unsupported`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// use positioned_io::{Advice, RandomAccessFile, WriteAt};
+///
+/// # fn foo() -> io::Result<()> {
+/// let mut file = RandomAccessFile::options()
+///     .read(true)
+///     .write(true)
+///     .create(true)
+///     .advice(Some(Advice::Sequential))
+///     .preallocate(1 << 20)
+///     .open("out.bin")?;
+/// file.write_all_at(1 << 19, b"middle")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RandomAccessFileOptions {
+    options: OpenOptions,
+    advice: Option<Advice>,
+    noatime: bool,
+    direct: bool,
+    dsync: bool,
+    preallocate: u64,
+}
+
+impl RandomAccessFileOptions {
+    /// Creates a blank set of options.
+    ///
+    /// Nothing is enabled, except for advising random access (`FADV_RANDOM`)
+    /// like [`RandomAccessFile::try_new()`](struct.RandomAccessFile.html#method.try_new).
+    pub fn new() -> RandomAccessFileOptions {
+        RandomAccessFileOptions {
+            options: OpenOptions::new(),
+            advice: Some(Advice::Random),
+            noatime: false,
+            direct: false,
+            dsync: false,
+            preallocate: 0,
+        }
+    }
+
+    /// Sets whether the file can be read.
+    pub fn read(&mut self, read: bool) -> &mut Self {
+        self.options.read(read);
+        self
+    }
+
+    /// Sets whether the file can be written.
+    pub fn write(&mut self, write: bool) -> &mut Self {
+        self.options.write(write);
+        self
+    }
+
+    /// Sets whether to create the file if it doesn't exist.
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.options.create(create);
+        self
+    }
+
+    /// Sets whether to create the file, failing if it already exists.
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.options.create_new(create_new);
+        self
+    }
+
+    /// Sets whether to truncate the file to zero length when it's opened.
+    pub fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.options.truncate(truncate);
+        self
+    }
+
+    /// Sets the access pattern to advise for the whole file, or `None` to
+    /// leave the operating system's default.
+    ///
+    /// See [`ReadAt::advise()`](trait.ReadAt.html#method.advise).
+    pub fn advice(&mut self, advice: Option<Advice>) -> &mut Self {
+        self.advice = advice;
+        self
+    }
+
+    /// Sets whether to skip updating the access time on reads (`O_NOATIME`).
+    ///
+    /// Only the owner of a file may do this, so for other files it's quietly
+    /// ignored.
+    pub fn noatime(&mut self, noatime: bool) -> &mut Self {
+        self.noatime = noatime;
+        self
+    }
+
+    /// Sets whether to bypass the page cache (`O_DIRECT`).
+    ///
+    /// Buffers, offsets and lengths must then be aligned as reported by
+    /// [`Geometry::geometry()`](trait.Geometry.html#tymethod.geometry).
+    pub fn direct(&mut self, direct: bool) -> &mut Self {
+        self.direct = direct;
+        self
+    }
+
+    /// Sets whether each write waits for its data to reach storage
+    /// (`O_DSYNC`).
+    pub fn dsync(&mut self, dsync: bool) -> &mut Self {
+        self.dsync = dsync;
+        self
+    }
+
+    /// Sets how many bytes to allocate for the file when it's opened, as with
+    /// [`Allocate::preallocate()`](trait.Allocate.html#tymethod.preallocate).
+    ///
+    /// The file is extended if it's shorter, which needs write access. Where
+    /// the platform or file system can't reserve space, the file is only
+    /// extended, as with `set_len()`.
+    pub fn preallocate(&mut self, len: u64) -> &mut Self {
+        self.preallocate = len;
+        self
+    }
+
+    /// Opens a file at `path` with these options.
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> io::Result<RandomAccessFile> {
+        let file = self.open_file(path.as_ref())?;
+        let mut raf = RandomAccessFile::with_advice(file, self.advice)?;
+        if self.preallocate > 0 {
+            match raf.preallocate(0, self.preallocate) {
+                Err(err) if err.kind() == io::ErrorKind::Unsupported => {
+                    if raf.file.metadata()?.len() < self.preallocate {
+                        raf.file.set_len(self.preallocate)?;
+                    }
+                }
+                res => res?,
+            }
+        }
+        Ok(raf)
+    }
+
+    #[cfg(unix)]
+    fn open_file(&self, path: &Path) -> io::Result<File> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let flags = crate::unix::open_flags(false, self.direct, self.dsync)?;
+        if self.noatime {
+            let noatime = crate::unix::open_flags(true, self.direct, self.dsync)?;
+            match self.options.clone().custom_flags(noatime).open(path) {
+                // O_NOATIME is refused for files owned by someone else.
+                Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {}
+                result => return result,
+            }
+        }
+        self.options.clone().custom_flags(flags).open(path)
+    }
+
+    #[cfg(not(unix))]
+    fn open_file(&self, path: &Path) -> io::Result<File> {
+        if self.noatime || self.direct || self.dsync {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "noatime, direct and dsync aren't supported on this platform",
+            ));
+        }
+        self.options.open(path)
+    }
+}
+
+impl Default for RandomAccessFileOptions {
+    fn default() -> RandomAccessFileOptions {
+        RandomAccessFileOptions::new()
+    }
+}
+
+#[cfg(unix)]
+impl AsFd for RandomAccessFile {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+#[cfg(unix)]
+impl AsRawFd for RandomAccessFile {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+#[cfg(unix)]
+impl From<RandomAccessFile> for OwnedFd {
+    fn from(raf: RandomAccessFile) -> OwnedFd {
+        raf.file.into()
+    }
+}
+
+#[cfg(unix)]
+impl TryFrom<OwnedFd> for RandomAccessFile {
+    type Error = io::Error;
+
+    fn try_from(fd: OwnedFd) -> io::Result<RandomAccessFile> {
+        RandomAccessFile::try_new(fd.into())
+    }
+}
+
+#[cfg(windows)]
+impl AsHandle for RandomAccessFile {
+    fn as_handle(&self) -> BorrowedHandle<'_> {
+        self.file.as_handle()
+    }
+}
+
+#[cfg(windows)]
+impl AsRawHandle for RandomAccessFile {
+    fn as_raw_handle(&self) -> RawHandle {
+        self.file.as_raw_handle()
+    }
+}
+
+/// The file position is left wherever the `RandomAccessFile` moved it. Use
+/// [`try_into_inner()`](struct.RandomAccessFile.html#method.try_into_inner)
+/// to restore it.
+#[cfg(windows)]
+impl From<RandomAccessFile> for OwnedHandle {
+    fn from(raf: RandomAccessFile) -> OwnedHandle {
+        raf.file.into()
+    }
+}
+
+#[cfg(windows)]
+impl TryFrom<OwnedHandle> for RandomAccessFile {
+    type Error = io::Error;
+
+    fn try_from(handle: OwnedHandle) -> io::Result<RandomAccessFile> {
+        RandomAccessFile::try_new(handle.into())
+    }
+}
+
 #[cfg(unix)]
 impl ReadAt for RandomAccessFile {
     #[inline]
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         FileExt::read_at(&self.file, buf, pos)
     }
+
+    #[inline]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        crate::unix::read_vectored_at(&self.file, pos, bufs)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[inline]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        crate::unix::read_many_at(&self.file, requests)
+    }
+
+    #[inline]
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        crate::unix::read_buf_at(&self.file, pos, buf)
+    }
+
+    #[inline]
+    fn as_read_file(&self) -> Option<&File> {
+        Some(&self.file)
+    }
+
+    #[inline]
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        crate::unix::advise(&self.file, pos, len, advice)
+    }
 }
 
 #[cfg(unix)]
-impl WriteAt for &RandomAccessFile {
-    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
-        FileExt::write_at(&self.file, buf, pos)
+impl WriteAtShared for RandomAccessFile {
+    #[inline]
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        WriteAtShared::write_at(&self.file, pos, buf)
     }
 
-    fn flush(&mut self) -> io::Result<()> {
+    #[inline]
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        WriteAtShared::write_vectored_at(&self.file, pos, bufs)
+    }
+
+    #[inline]
+    fn as_write_file(&self) -> Option<&File> {
+        Some(&self.file)
+    }
+
+    fn flush(&self) -> io::Result<()> {
         Write::flush(&mut &self.file)
     }
 }
@@ -123,26 +448,44 @@ impl ReadAt for RandomAccessFile {
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         FileExt::seek_read(&self.file, buf, pos)
     }
+
+    #[inline]
+    fn as_read_file(&self) -> Option<&File> {
+        Some(&self.file)
+    }
 }
 
 #[cfg(windows)]
-impl WriteAt for &RandomAccessFile {
-    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+impl WriteAtShared for RandomAccessFile {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
         FileExt::seek_write(&self.file, buf, pos)
     }
 
-    fn flush(&mut self) -> io::Result<()> {
+    #[inline]
+    fn as_write_file(&self) -> Option<&File> {
+        Some(&self.file)
+    }
+
+    fn flush(&self) -> io::Result<()> {
         Write::flush(&mut &self.file)
     }
 }
 
 impl WriteAt for RandomAccessFile {
     fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
-        WriteAt::write_at(&mut &*self, pos, buf)
+        WriteAtShared::write_at(self, pos, buf)
+    }
+
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        WriteAtShared::write_vectored_at(self, pos, bufs)
+    }
+
+    fn as_write_file(&self) -> Option<&File> {
+        Some(&self.file)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        WriteAt::flush(&mut &*self)
+        WriteAtShared::flush(self)
     }
 }
 
@@ -152,16 +495,59 @@ impl Size for RandomAccessFile {
     }
 }
 
-impl ReadAt for Arc<RandomAccessFile> {
-    #[inline]
-    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
-        (**self).read_at(pos, buf)
+impl SetLen for RandomAccessFile {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        self.file.set_len(len)
     }
 }
 
-impl Size for Arc<RandomAccessFile> {
-    #[inline]
-    fn size(&self) -> io::Result<Option<u64>> {
-        (**self).size()
+impl Allocate for RandomAccessFile {
+    fn preallocate(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        self.file.preallocate(pos, len)
+    }
+
+    fn punch_hole(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        self.file.punch_hole(pos, len)
+    }
+
+    fn zero_range(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        self.file.zero_range(pos, len)
+    }
+}
+
+impl Durable for RandomAccessFile {
+    fn sync_data(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        self.file.sync_all()
+    }
+
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        self.file.sync_range(pos, len)
+    }
+}
+
+impl Geometry for RandomAccessFile {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        Ok(Capabilities {
+            concurrent_writes: true,
+            ..self.file.geometry()?
+        })
+    }
+}
+
+impl Sparse for RandomAccessFile {
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        self.file.next_data(pos)
+    }
+
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        self.file.next_hole(pos)
+    }
+
+    fn allocated_size(&self) -> io::Result<Option<u64>> {
+        self.file.allocated_size()
     }
 }
@@ -1,17 +1,97 @@
-use std::{cell::RefCell, io};
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, boxed::Box, rc::Rc, vec::Vec};
+use core::cell::RefCell;
+#[cfg(feature = "std")]
+use std::{
+    fs::File,
+    sync::{Arc, Mutex, RwLock},
+};
 
-use super::{ReadAt, Size, WriteAt};
+#[cfg(feature = "std")]
+use super::io::{IoSlice, IoSliceMut};
+use super::{
+    Advice, Allocate, Capabilities, Durable, ErrorType, Geometry, ReadAt, ReadAtRef, ReadBuf,
+    SetLen, Size, Sparse, TryReadAt, TryWriteAt, WriteAt, WriteAtShared, io,
+};
 
 impl<R: ReadAt + ?Sized> ReadAt for &R {
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         R::read_at(self, pos, buf)
     }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        R::read_vectored_at(self, pos, bufs)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        R::read_many_at(self, requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        R::read_buf_at(self, pos, buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        R::read_cow_at(self, pos, len)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_read_file(&self) -> Option<&File> {
+        R::as_read_file(self)
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        R::advise(self, pos, len, advice)
+    }
 }
 
 impl<R: ReadAt + ?Sized> ReadAt for &mut R {
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         R::read_at(self, pos, buf)
     }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        R::read_vectored_at(self, pos, bufs)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        R::read_many_at(self, requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        R::read_buf_at(self, pos, buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        R::read_cow_at(self, pos, len)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_read_file(&self) -> Option<&File> {
+        R::as_read_file(self)
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        R::advise(self, pos, len, advice)
+    }
+}
+
+impl<R: ReadAtRef + ?Sized> ReadAtRef for &R {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        R::read_ref_at(self, pos, len)
+    }
+}
+
+impl<R: ReadAtRef + ?Sized> ReadAtRef for &mut R {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        R::read_ref_at(self, pos, len)
+    }
 }
 
 impl<W: WriteAt + ?Sized> WriteAt for &mut W {
@@ -19,6 +99,16 @@ impl<W: WriteAt + ?Sized> WriteAt for &mut W {
         W::write_at(self, pos, buf)
     }
 
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        W::write_vectored_at(self, pos, bufs)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_write_file(&self) -> Option<&File> {
+        W::as_write_file(self)
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         W::flush(self)
     }
@@ -35,46 +125,832 @@ impl<S: Size + ?Sized> Size for &mut S {
     }
 }
 
+impl<S: Sparse + ?Sized> Sparse for &S {
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        S::next_data(self, pos)
+    }
+
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        S::next_hole(self, pos)
+    }
+
+    fn allocated_size(&self) -> io::Result<Option<u64>> {
+        S::allocated_size(self)
+    }
+}
+
+impl<S: Sparse + ?Sized> Sparse for &mut S {
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        S::next_data(self, pos)
+    }
+
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        S::next_hole(self, pos)
+    }
+
+    fn allocated_size(&self) -> io::Result<Option<u64>> {
+        S::allocated_size(self)
+    }
+}
+
+impl<D: Durable + ?Sized> Durable for &D {
+    fn sync_data(&self) -> io::Result<()> {
+        D::sync_data(self)
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        D::sync_all(self)
+    }
+
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        D::sync_range(self, pos, len)
+    }
+}
+
+impl<G: Geometry + ?Sized> Geometry for &G {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        G::geometry(self)
+    }
+}
+
+impl<D: Durable + ?Sized> Durable for &mut D {
+    fn sync_data(&self) -> io::Result<()> {
+        D::sync_data(self)
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        D::sync_all(self)
+    }
+
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        D::sync_range(self, pos, len)
+    }
+}
+
+impl<G: Geometry + ?Sized> Geometry for &mut G {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        G::geometry(self)
+    }
+}
+
+impl<S: SetLen + ?Sized> SetLen for &mut S {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        S::set_len(self, len)
+    }
+}
+
+impl<A: Allocate + ?Sized> Allocate for &mut A {
+    fn preallocate(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        A::preallocate(self, pos, len)
+    }
+
+    fn punch_hole(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        A::punch_hole(self, pos, len)
+    }
+
+    fn zero_range(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        A::zero_range(self, pos, len)
+    }
+}
+
 impl<R: ReadAt> ReadAt for &RefCell<R> {
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         self.borrow().read_at(pos, buf)
     }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.borrow().read_vectored_at(pos, bufs)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        self.borrow().read_many_at(requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.borrow().read_buf_at(pos, buf)
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.borrow().advise(pos, len, advice)
+    }
 }
 
-impl<W: WriteAt> WriteAt for &RefCell<W> {
-    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+impl<W: WriteAt> WriteAtShared for RefCell<W> {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
         self.borrow_mut().write_at(pos, buf)
     }
 
-    fn flush(&mut self) -> io::Result<()> {
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.borrow_mut().write_vectored_at(pos, bufs)
+    }
+
+    fn flush(&self) -> io::Result<()> {
         self.borrow_mut().flush()
     }
 }
 
+#[cfg(feature = "std")]
+impl<W: WriteAt> WriteAtShared for Mutex<W> {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.lock().map_err(|_| poisoned())?.write_at(pos, buf)
+    }
+
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.lock()
+            .map_err(|_| poisoned())?
+            .write_vectored_at(pos, bufs)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.lock().map_err(|_| poisoned())?.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: WriteAt> WriteAtShared for RwLock<W> {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.write().map_err(|_| poisoned())?.write_at(pos, buf)
+    }
+
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.write()
+            .map_err(|_| poisoned())?
+            .write_vectored_at(pos, bufs)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.write().map_err(|_| poisoned())?.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+fn poisoned() -> io::Error {
+    io::Error::other("lock poisoned by a panicked writer")
+}
+
+#[cfg(feature = "std")]
+impl<R: ReadAt> ReadAt for Mutex<R> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.lock().map_err(|_| poisoned())?.read_at(pos, buf)
+    }
+
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.lock()
+            .map_err(|_| poisoned())?
+            .read_vectored_at(pos, bufs)
+    }
+
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        match self.lock() {
+            Ok(io) => io.read_many_at(requests),
+            Err(_) => requests.iter().map(|_| Err(poisoned())).collect(),
+        }
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.lock().map_err(|_| poisoned())?.read_buf_at(pos, buf)
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.lock()
+            .map_err(|_| poisoned())?
+            .advise(pos, len, advice)
+    }
+}
+
+// Owning the lock means no locking is needed.
+#[cfg(feature = "std")]
+impl<W: WriteAt> WriteAt for Mutex<W> {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.get_mut().map_err(|_| poisoned())?.write_at(pos, buf)
+    }
+
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.get_mut()
+            .map_err(|_| poisoned())?
+            .write_vectored_at(pos, bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.get_mut().map_err(|_| poisoned())?.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: Size> Size for Mutex<S> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        self.lock().map_err(|_| poisoned())?.size()
+    }
+}
+
+/// Writes through a shared reference take the lock, so they can come from
+/// several threads at once.
+#[cfg(feature = "std")]
+impl<G: Geometry> Geometry for Mutex<G> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        let mut caps = self.lock().map_err(|_| poisoned())?.geometry()?;
+        caps.concurrent_writes = true;
+        Ok(caps)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: ReadAt> ReadAt for RwLock<R> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.read().map_err(|_| poisoned())?.read_at(pos, buf)
+    }
+
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.read()
+            .map_err(|_| poisoned())?
+            .read_vectored_at(pos, bufs)
+    }
+
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        match self.read() {
+            Ok(io) => io.read_many_at(requests),
+            Err(_) => requests.iter().map(|_| Err(poisoned())).collect(),
+        }
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.read().map_err(|_| poisoned())?.read_buf_at(pos, buf)
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.read()
+            .map_err(|_| poisoned())?
+            .advise(pos, len, advice)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: WriteAt> WriteAt for RwLock<W> {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.get_mut().map_err(|_| poisoned())?.write_at(pos, buf)
+    }
+
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.get_mut()
+            .map_err(|_| poisoned())?
+            .write_vectored_at(pos, bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.get_mut().map_err(|_| poisoned())?.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: Size> Size for RwLock<S> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        self.read().map_err(|_| poisoned())?.size()
+    }
+}
+
+/// As with `Mutex`, each write takes the write lock.
+#[cfg(feature = "std")]
+impl<G: Geometry> Geometry for RwLock<G> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        let mut caps = self.read().map_err(|_| poisoned())?.geometry()?;
+        caps.concurrent_writes = true;
+        Ok(caps)
+    }
+}
+
+impl<W: WriteAtShared + ?Sized> WriteAtShared for &W {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        W::write_at(self, pos, buf)
+    }
+
+    fn write_all_at(&self, pos: u64, buf: &[u8]) -> io::Result<()> {
+        W::write_all_at(self, pos, buf)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        W::write_vectored_at(self, pos, bufs)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_write_file(&self) -> Option<&File> {
+        W::as_write_file(self)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        W::flush(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: WriteAtShared + ?Sized> WriteAtShared for Box<W> {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        (**self).write_at(pos, buf)
+    }
+
+    fn write_all_at(&self, pos: u64, buf: &[u8]) -> io::Result<()> {
+        (**self).write_all_at(pos, buf)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (**self).write_vectored_at(pos, bufs)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_write_file(&self) -> Option<&File> {
+        (**self).as_write_file()
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+// Anything that can be written through a shared reference.
+impl<W: WriteAtShared + ?Sized> WriteAt for &W {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        W::write_at(self, pos, buf)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        W::write_vectored_at(self, pos, bufs)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_write_file(&self) -> Option<&File> {
+        W::as_write_file(self)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        W::flush(self)
+    }
+}
+
 impl<S: Size> Size for &RefCell<S> {
     fn size(&self) -> io::Result<Option<u64>> {
         self.borrow().size()
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<R: ReadAt + ?Sized> ReadAt for Box<R> {
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         (**self).read_at(pos, buf)
     }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        (**self).read_vectored_at(pos, bufs)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        (**self).read_many_at(requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        (**self).read_buf_at(pos, buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        (**self).read_cow_at(pos, len)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_read_file(&self) -> Option<&File> {
+        (**self).as_read_file()
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        (**self).advise(pos, len, advice)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: ReadAtRef + ?Sized> ReadAtRef for Box<R> {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        (**self).read_ref_at(pos, len)
+    }
 }
 
+#[cfg(feature = "alloc")]
 impl<R: WriteAt + ?Sized> WriteAt for Box<R> {
     fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
         (**self).write_at(pos, buf)
     }
 
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (**self).write_vectored_at(pos, bufs)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_write_file(&self) -> Option<&File> {
+        (**self).as_write_file()
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         (**self).flush()
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<S: Size + ?Sized> Size for Box<S> {
     fn size(&self) -> io::Result<Option<u64>> {
         (**self).size()
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<S: SetLen + ?Sized> SetLen for Box<S> {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        (**self).set_len(len)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A: Allocate + ?Sized> Allocate for Box<A> {
+    fn preallocate(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        (**self).preallocate(pos, len)
+    }
+
+    fn punch_hole(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        (**self).punch_hole(pos, len)
+    }
+
+    fn zero_range(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        (**self).zero_range(pos, len)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Sparse + ?Sized> Sparse for Box<S> {
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        (**self).next_data(pos)
+    }
+
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        (**self).next_hole(pos)
+    }
+
+    fn allocated_size(&self) -> io::Result<Option<u64>> {
+        (**self).allocated_size()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D: Durable + ?Sized> Durable for Box<D> {
+    fn sync_data(&self) -> io::Result<()> {
+        (**self).sync_data()
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        (**self).sync_all()
+    }
+
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        (**self).sync_range(pos, len)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<G: Geometry + ?Sized> Geometry for Box<G> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        G::geometry(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: ReadAt + ?Sized> ReadAt for Arc<R> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read_at(pos, buf)
+    }
+
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        (**self).read_vectored_at(pos, bufs)
+    }
+
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        (**self).read_many_at(requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        (**self).read_buf_at(pos, buf)
+    }
+
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        (**self).read_cow_at(pos, len)
+    }
+
+    fn as_read_file(&self) -> Option<&File> {
+        (**self).as_read_file()
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        (**self).advise(pos, len, advice)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: ReadAtRef + ?Sized> ReadAtRef for Arc<R> {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        (**self).read_ref_at(pos, len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: Size + ?Sized> Size for Arc<S> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        (**self).size()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: Sparse + ?Sized> Sparse for Arc<S> {
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        (**self).next_data(pos)
+    }
+
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        (**self).next_hole(pos)
+    }
+
+    fn allocated_size(&self) -> io::Result<Option<u64>> {
+        (**self).allocated_size()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<D: Durable + ?Sized> Durable for Arc<D> {
+    fn sync_data(&self) -> io::Result<()> {
+        (**self).sync_data()
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        (**self).sync_all()
+    }
+
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        (**self).sync_range(pos, len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<G: Geometry + ?Sized> Geometry for Arc<G> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        G::geometry(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: WriteAtShared + ?Sized> WriteAtShared for Arc<W> {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        (**self).write_at(pos, buf)
+    }
+
+    fn write_all_at(&self, pos: u64, buf: &[u8]) -> io::Result<()> {
+        (**self).write_all_at(pos, buf)
+    }
+
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (**self).write_vectored_at(pos, bufs)
+    }
+
+    fn as_write_file(&self) -> Option<&File> {
+        (**self).as_write_file()
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: WriteAtShared + ?Sized> WriteAt for Arc<W> {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        (**self).write_at(pos, buf)
+    }
+
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (**self).write_vectored_at(pos, bufs)
+    }
+
+    fn as_write_file(&self) -> Option<&File> {
+        (**self).as_write_file()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: ReadAt + ?Sized> ReadAt for Rc<R> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read_at(pos, buf)
+    }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        (**self).read_vectored_at(pos, bufs)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        (**self).read_many_at(requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        (**self).read_buf_at(pos, buf)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        (**self).read_cow_at(pos, len)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_read_file(&self) -> Option<&File> {
+        (**self).as_read_file()
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        (**self).advise(pos, len, advice)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: ReadAtRef + ?Sized> ReadAtRef for Rc<R> {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        (**self).read_ref_at(pos, len)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Size + ?Sized> Size for Rc<S> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        (**self).size()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S: Sparse + ?Sized> Sparse for Rc<S> {
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        (**self).next_data(pos)
+    }
+
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        (**self).next_hole(pos)
+    }
+
+    fn allocated_size(&self) -> io::Result<Option<u64>> {
+        (**self).allocated_size()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<D: Durable + ?Sized> Durable for Rc<D> {
+    fn sync_data(&self) -> io::Result<()> {
+        (**self).sync_data()
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        (**self).sync_all()
+    }
+
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        (**self).sync_range(pos, len)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<G: Geometry + ?Sized> Geometry for Rc<G> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        G::geometry(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: WriteAtShared + ?Sized> WriteAtShared for Rc<W> {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        (**self).write_at(pos, buf)
+    }
+
+    fn write_all_at(&self, pos: u64, buf: &[u8]) -> io::Result<()> {
+        (**self).write_all_at(pos, buf)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (**self).write_vectored_at(pos, bufs)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_write_file(&self) -> Option<&File> {
+        (**self).as_write_file()
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: WriteAtShared + ?Sized> WriteAt for Rc<W> {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        (**self).write_at(pos, buf)
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        (**self).write_vectored_at(pos, bufs)
+    }
+
+    #[cfg(feature = "std")]
+    fn as_write_file(&self) -> Option<&File> {
+        (**self).as_write_file()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &T {
+    type Error = T::Error;
+}
+
+impl<T: ErrorType + ?Sized> ErrorType for &mut T {
+    type Error = T::Error;
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ErrorType + ?Sized> ErrorType for Box<T> {
+    type Error = T::Error;
+}
+
+impl<R: TryReadAt + ?Sized> TryReadAt for &R {
+    fn try_read_at(&self, pos: u64, buf: &mut [u8]) -> Result<usize, R::Error> {
+        R::try_read_at(self, pos, buf)
+    }
+
+    fn try_read_exact_at(&self, pos: u64, buf: &mut [u8]) -> Result<(), R::Error> {
+        R::try_read_exact_at(self, pos, buf)
+    }
+}
+
+impl<R: TryReadAt + ?Sized> TryReadAt for &mut R {
+    fn try_read_at(&self, pos: u64, buf: &mut [u8]) -> Result<usize, R::Error> {
+        R::try_read_at(self, pos, buf)
+    }
+
+    fn try_read_exact_at(&self, pos: u64, buf: &mut [u8]) -> Result<(), R::Error> {
+        R::try_read_exact_at(self, pos, buf)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: TryReadAt + ?Sized> TryReadAt for Box<R> {
+    fn try_read_at(&self, pos: u64, buf: &mut [u8]) -> Result<usize, R::Error> {
+        (**self).try_read_at(pos, buf)
+    }
+
+    fn try_read_exact_at(&self, pos: u64, buf: &mut [u8]) -> Result<(), R::Error> {
+        (**self).try_read_exact_at(pos, buf)
+    }
+}
+
+impl<W: TryWriteAt + ?Sized> TryWriteAt for &mut W {
+    fn try_write_at(&mut self, pos: u64, buf: &[u8]) -> Result<usize, W::Error> {
+        W::try_write_at(self, pos, buf)
+    }
+
+    fn try_write_all_at(&mut self, pos: u64, buf: &[u8]) -> Result<(), W::Error> {
+        W::try_write_all_at(self, pos, buf)
+    }
+
+    fn try_flush(&mut self) -> Result<(), W::Error> {
+        W::try_flush(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W: TryWriteAt + ?Sized> TryWriteAt for Box<W> {
+    fn try_write_at(&mut self, pos: u64, buf: &[u8]) -> Result<usize, W::Error> {
+        (**self).try_write_at(pos, buf)
+    }
+
+    fn try_write_all_at(&mut self, pos: u64, buf: &[u8]) -> Result<(), W::Error> {
+        (**self).try_write_all_at(pos, buf)
+    }
+
+    fn try_flush(&mut self) -> Result<(), W::Error> {
+        (**self).try_flush()
+    }
+}
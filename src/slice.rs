@@ -1,6 +1,13 @@
-use std::{cmp::min, io};
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, vec::Vec};
+use core::cmp::min;
 
-use super::{ReadAt, Size, WriteAt};
+#[cfg(feature = "std")]
+use super::io::{IoSlice, IoSliceMut};
+use super::{
+    Advice, Allocate, Capabilities, Durable, ErrorType, Geometry, ReadAt, ReadAtRef, ReadBuf,
+    SetLen, Size, Sparse, TryReadAt, TryWriteAt, WriteAt, WriteAtShared, error, io,
+};
 
 /// A window into another `ReadAt` or `WriteAt`.
 ///
@@ -66,13 +73,28 @@ impl<I> Slice<I> {
 
     /// Get the available bytes starting at some point.
     fn avail(&self, pos: u64, bytes: usize) -> usize {
+        self.avail_len(pos, bytes as u64) as usize
+    }
+
+    /// Like `avail()`, for ranges that need not fit in memory.
+    pub(crate) fn avail_len(&self, pos: u64, len: u64) -> u64 {
         match self.size {
-            None => bytes,
+            None => len,
             Some(size) if pos >= size => 0,
-            Some(size) => min(bytes as u64, size - pos) as usize,
+            Some(size) => min(len, size - pos),
         }
     }
 
+    /// Translates a position in the slice to one in the underlying I/O.
+    pub(crate) fn inner_pos(&self, pos: u64) -> io::Result<u64> {
+        pos.checked_add(self.offset).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "position overflows slice offset",
+            )
+        })
+    }
+
     /// Consumes the slice, returning the underlying value.
     pub fn into_inner(self) -> I {
         self.io
@@ -105,7 +127,13 @@ impl<I: Size> Slice<I> {
     /// `new()`, but it won't do you any good for reading.
     pub fn new_to_end(io: I, offset: u64) -> io::Result<Self> {
         match io.size() {
-            Ok(Some(size)) => Ok(Self::new(io, offset, Some(size - offset))),
+            Ok(Some(size)) => match size.checked_sub(offset) {
+                Some(size) => Ok(Self::new(io, offset, Some(size))),
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "offset past the end",
+                )),
+            },
             _ => Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 "unknown base size",
@@ -117,14 +145,149 @@ impl<I: Size> Slice<I> {
 impl<I: ReadAt> ReadAt for Slice<I> {
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         let bytes = self.avail(pos, buf.len());
-        self.io.read_at(pos + self.offset, &mut buf[..bytes])
+        if bytes == 0 {
+            return Ok(0);
+        }
+        let pos = self.inner_pos(pos)?;
+        self.io
+            .read_at(pos, &mut buf[..bytes])
+            .map_err(|e| error::context(e, "Slice::read_at", pos, bytes as u64, 0))
+    }
+
+    #[cfg(feature = "std")]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let total = bufs.iter().fold(0usize, |n, b| n.saturating_add(b.len()));
+        let mut bytes = self.avail(pos, total);
+        if bytes == 0 {
+            return Ok(0);
+        }
+        let pos = self.inner_pos(pos)?;
+        if bytes == total {
+            return self.io.read_vectored_at(pos, bufs);
+        }
+
+        // Trim the buffers so they fit within the slice.
+        let mut limited = Vec::new();
+        for buf in bufs.iter_mut() {
+            if bytes == 0 {
+                break;
+            }
+            let n = min(bytes, buf.len());
+            limited.push(IoSliceMut::new(&mut buf[..n]));
+            bytes -= n;
+        }
+        self.io.read_vectored_at(pos, &mut limited)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        let mut invalid = Vec::new();
+        let mut translated: Vec<(u64, &mut [u8])> = requests
+            .iter_mut()
+            .enumerate()
+            .map(|(i, (pos, buf))| {
+                let bytes = self.avail(*pos, buf.len());
+                match self.inner_pos(*pos) {
+                    _ if bytes == 0 => (self.offset, &mut buf[..0]),
+                    Ok(pos) => (pos, &mut buf[..bytes]),
+                    Err(e) => {
+                        // Read nothing, and report the error afterwards.
+                        invalid.push((i, e));
+                        (self.offset, &mut buf[..0])
+                    }
+                }
+            })
+            .collect();
+        let mut results = self.io.read_many_at(&mut translated);
+        for (i, e) in invalid {
+            results[i] = Err(e);
+        }
+        results
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        let bytes = self.avail(pos, buf.remaining());
+        if bytes == 0 {
+            return Ok(0);
+        }
+        let pos = self.inner_pos(pos)?;
+        if bytes == buf.remaining() {
+            return self.io.read_buf_at(pos, buf);
+        }
+
+        // Read into a smaller buffer that fits within the slice.
+        // Safety: the inner buffer never de-initializes bytes.
+        let unfilled = unsafe { buf.unfilled_mut() };
+        let mut limited = ReadBuf::new(&mut unfilled[..bytes]);
+        let memory = limited.memory();
+        let read = self.io.read_buf_at(pos, &mut limited);
+        limited.check_memory(memory)?;
+        read?;
+        let filled = limited.len();
+        // Safety: the inner buffer is still part of ours, and filled these
+        // bytes.
+        unsafe { buf.assume_filled(filled) };
+        Ok(filled)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        let bytes = self.avail(pos, len);
+        if bytes == 0 {
+            return Ok(Cow::Borrowed(&[]));
+        }
+        self.io.read_cow_at(self.inner_pos(pos)?, bytes)
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        // A length of zero covers the rest of the slice, if it has an end.
+        let len = match len {
+            0 if self.size.is_none() => 0,
+            0 => self.avail_len(pos, u64::MAX),
+            _ => self.avail_len(pos, len),
+        };
+        if len == 0 && self.size.is_some() {
+            return Ok(());
+        }
+        self.io.advise(self.inner_pos(pos)?, len, advice)
+    }
+}
+
+impl<I: ReadAtRef> ReadAtRef for Slice<I> {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        let bytes = self.avail(pos, len);
+        if bytes == 0 {
+            return Ok(&[]);
+        }
+        self.io.read_ref_at(self.inner_pos(pos)?, bytes)
     }
 }
 
 impl<I: WriteAt> WriteAt for Slice<I> {
     fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
         let bytes = self.avail(pos, buf.len());
-        self.io.write_at(pos + self.offset, &buf[..bytes])
+        if bytes == 0 {
+            return Ok(0);
+        }
+        let pos = self.inner_pos(pos)?;
+        self.io
+            .write_at(pos, &buf[..bytes])
+            .map_err(|e| error::context(e, "Slice::write_at", pos, bytes as u64, 0))
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let total = bufs.iter().fold(0usize, |n, b| n.saturating_add(b.len()));
+        let bytes = self.avail(pos, total);
+        if bytes == 0 {
+            return Ok(0);
+        }
+        let pos = self.inner_pos(pos)?;
+        if bytes == total {
+            return self.io.write_vectored_at(pos, bufs);
+        }
+
+        self.io.write_vectored_at(pos, &limit_bufs(bufs, bytes))
     }
 
     fn flush(&mut self) -> io::Result<()> {
@@ -132,8 +295,200 @@ impl<I: WriteAt> WriteAt for Slice<I> {
     }
 }
 
+impl<I: WriteAtShared> WriteAtShared for Slice<I> {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        let bytes = self.avail(pos, buf.len());
+        if bytes == 0 {
+            return Ok(0);
+        }
+        let pos = self.inner_pos(pos)?;
+        self.io
+            .write_at(pos, &buf[..bytes])
+            .map_err(|e| error::context(e, "Slice::write_at", pos, bytes as u64, 0))
+    }
+
+    #[cfg(feature = "std")]
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let total = bufs.iter().fold(0usize, |n, b| n.saturating_add(b.len()));
+        let bytes = self.avail(pos, total);
+        if bytes == 0 {
+            return Ok(0);
+        }
+        let pos = self.inner_pos(pos)?;
+        if bytes == total {
+            return self.io.write_vectored_at(pos, bufs);
+        }
+        self.io.write_vectored_at(pos, &limit_bufs(bufs, bytes))
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+// Trim the buffers so they hold no more than `bytes` in total.
+#[cfg(feature = "std")]
+fn limit_bufs<'a>(bufs: &'a [IoSlice<'_>], mut bytes: usize) -> Vec<IoSlice<'a>> {
+    let mut limited = Vec::new();
+    for buf in bufs {
+        if bytes == 0 {
+            break;
+        }
+        let n = min(bytes, buf.len());
+        limited.push(IoSlice::new(&buf[..n]));
+        bytes -= n;
+    }
+    limited
+}
+
+impl<I: ErrorType> ErrorType for Slice<I> {
+    type Error = I::Error;
+}
+
+impl<I: TryReadAt> TryReadAt for Slice<I> {
+    fn try_read_at(&self, pos: u64, buf: &mut [u8]) -> Result<usize, I::Error> {
+        let bytes = self.avail(pos, buf.len());
+        if bytes == 0 {
+            return Ok(0);
+        }
+        let pos = self.inner_pos(pos).map_err(|e| e.kind())?;
+        self.io.try_read_at(pos, &mut buf[..bytes])
+    }
+}
+
+impl<I: TryWriteAt> TryWriteAt for Slice<I> {
+    fn try_write_at(&mut self, pos: u64, buf: &[u8]) -> Result<usize, I::Error> {
+        let bytes = self.avail(pos, buf.len());
+        if bytes == 0 {
+            return Ok(0);
+        }
+        let pos = self.inner_pos(pos).map_err(|e| e.kind())?;
+        self.io.try_write_at(pos, &buf[..bytes])
+    }
+
+    fn try_flush(&mut self) -> Result<(), I::Error> {
+        self.io.try_flush()
+    }
+}
+
 impl<I> Size for Slice<I> {
     fn size(&self) -> io::Result<Option<u64>> {
         Ok(self.size)
     }
 }
+
+/// Sets the size of the underlying I/O so that it ends `len` bytes into the
+/// slice, and resizes the slice to match.
+///
+/// If the slice has a size, `len` is clamped to it, so the underlying I/O is
+/// never extended past the end of the slice. Truncating would lose any data
+/// after the end of the slice, so if the underlying I/O extends further,
+/// `set_len()` fails with `ErrorKind::InvalidInput` instead.
+impl<I: SetLen + Size> SetLen for Slice<I> {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        let len = match self.size {
+            Some(size) => min(len, size),
+            None => len,
+        };
+        let end = self.inner_pos(len)?;
+        if let Some(size) = self.size {
+            let window_end = self.inner_pos(size)?;
+            if self.io.size()?.is_none_or(|size| size > window_end) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "underlying I/O extends past the end of the slice",
+                ));
+            }
+        }
+        self.io.set_len(end)?;
+        if self.size.is_some() {
+            self.size = Some(len);
+        }
+        Ok(())
+    }
+}
+
+/// Ranges are limited to the size of the slice, so the underlying I/O is
+/// never extended past the end of the slice.
+impl<I: Allocate> Allocate for Slice<I> {
+    fn preallocate(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        let len = self.avail_len(pos, len);
+        if len == 0 {
+            return Ok(());
+        }
+        self.io.preallocate(self.inner_pos(pos)?, len)
+    }
+
+    fn punch_hole(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        let len = self.avail_len(pos, len);
+        if len == 0 {
+            return Ok(());
+        }
+        self.io.punch_hole(self.inner_pos(pos)?, len)
+    }
+
+    fn zero_range(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        let len = self.avail_len(pos, len);
+        if len == 0 {
+            return Ok(());
+        }
+        self.io.zero_range(self.inner_pos(pos)?, len)
+    }
+}
+
+impl<I: Durable> Durable for Slice<I> {
+    fn sync_data(&self) -> io::Result<()> {
+        self.io.sync_data()
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        self.io.sync_all()
+    }
+
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        let len = self.avail_len(pos, len);
+        if len == 0 {
+            return Ok(());
+        }
+        self.io.sync_range(self.inner_pos(pos)?, len)
+    }
+}
+
+/// Alignments are reduced to those of the slice's offset, and a slice with a
+/// size never grows.
+impl<I: Geometry> Geometry for Slice<I> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        let mut caps = self.io.geometry()?.offset_by(self.offset);
+        caps.extendable &= self.size.is_none();
+        Ok(caps)
+    }
+}
+
+impl<I: Sparse> Sparse for Slice<I> {
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        if self.avail_len(pos, 1) == 0 {
+            return Ok(None);
+        }
+        let pos = self.inner_pos(pos)?;
+        match self.io.next_data(pos)? {
+            Some(data) => {
+                let data = data.max(pos) - self.offset;
+                Ok(Some(data).filter(|&data| self.avail_len(data, 1) > 0))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        if self.avail_len(pos, 1) == 0 {
+            // There are no holes past the end.
+            return Ok(pos);
+        }
+        let inner = self.inner_pos(pos)?;
+        let hole = self.io.next_hole(inner)?.max(inner) - self.offset;
+        match self.size {
+            Some(size) => Ok(min(hole, size)),
+            None => Ok(hole),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use core::cmp::min;
+
+use super::{ReadAt, Size, io};
+#[cfg(feature = "std")]
+use super::{SetLen, WriteAt, copy_range};
+
+/// Whether an [`Extent`](struct.Extent.html) holds data or is a hole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtentKind {
+    /// A range that may have storage allocated, and may contain non-zero
+    /// bytes.
+    Data,
+    /// A range without allocated storage, which reads as zeros.
+    Hole,
+}
+
+/// A range of an I/O object that is either all data or all hole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Extent {
+    /// Whether this range holds data or is a hole.
+    pub kind: ExtentKind,
+    /// The offset where this range starts.
+    pub pos: u64,
+    /// The length of this range, in bytes.
+    pub len: u64,
+}
+
+/// Trait to discover which ranges of an I/O object are actually allocated.
+///
+/// Sparse files can have ranges without any storage behind them, called
+/// holes, which read as zeros. Skipping holes allows scanning or copying a
+/// large, mostly empty file quickly.
+///
+/// The default methods treat the whole object as data, which is correct for
+/// anything that doesn't support holes. Files on Linux use
+/// [`lseek()`](http://man7.org/linux/man-pages/man2/lseek.2.html) with
+/// `SEEK_DATA` and `SEEK_HOLE`, on a private copy of the file opened through
+/// `/proc/self/fd`, so the file position is never moved. Each call opens a
+/// new copy, but [`Extents`](struct.Extents.html) and
+/// [`copy_sparse()`](fn.copy_sparse.html) open one for the whole scan. If
+/// the copy can't be opened, for example because `/proc` isn't mounted, they
+/// fail rather than report the file as all data.
+///
+/// Not every hole is necessarily reported, some file systems only detect
+/// holes in whole blocks, or not at all.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// use std::fs::File;
+/// use positioned_io::{ExtentKind, Sparse};
+///
+/// # fn foo() -> io::Result<()> {
+/// let file = File::open("disk.img")?;
+/// for extent in file.extents() {
+///     let extent = extent?;
+///     if extent.kind == ExtentKind::Data {
+///         println!("{} bytes of data at {}", extent.len, extent.pos);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub trait Sparse: ReadAt + Size {
+    /// Finds the start of the first data range at or after an offset.
+    ///
+    /// Returns `Ok(None)` if there is no more data before the end of the
+    /// object.
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        match self.size()? {
+            Some(size) if pos >= size => Ok(None),
+            _ => Ok(Some(pos)),
+        }
+    }
+
+    /// Finds the start of the first hole at or after an offset.
+    ///
+    /// The end of the object counts as a hole, so if there are no holes this
+    /// returns the size of the object.
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        match self.size()? {
+            Some(size) => Ok(size.max(pos)),
+            None => Ok(u64::MAX),
+        }
+    }
+
+    /// Gets the number of bytes of storage actually used by this object.
+    ///
+    /// This may be less than the size for sparse objects, or more if storage
+    /// was preallocated. The default implementation adds up the lengths of
+    /// all data ranges.
+    fn allocated_size(&self) -> io::Result<Option<u64>> {
+        if self.size()?.is_none() {
+            return Ok(None);
+        }
+        let mut total = 0;
+        for extent in Extents::new(self) {
+            let extent = extent?;
+            if extent.kind == ExtentKind::Data {
+                total += extent.len;
+            }
+        }
+        Ok(Some(total))
+    }
+
+    /// Iterates over the data and hole ranges of this object, in order.
+    ///
+    /// The object must have a known size.
+    fn extents(&self) -> Extents<'_, Self>
+    where
+        Self: Sized,
+    {
+        Extents::new(self)
+    }
+}
+
+/// An iterator over the data and hole ranges of an I/O object.
+///
+/// Created by [`Sparse::extents()`](trait.Sparse.html#method.extents).
+///
+/// If the object is backed by a file, as reported by
+/// [`ReadAt::as_read_file()`](trait.ReadAt.html#method.as_read_file), the
+/// file is searched directly.
+#[derive(Debug)]
+pub struct Extents<'a, S: ?Sized> {
+    io: &'a S,
+    pos: u64,
+    // Not known until the first call to next().
+    size: Option<u64>,
+    // A private copy of the underlying file, opened with the size.
+    #[cfg(all(feature = "std", any(target_os = "linux", target_os = "android")))]
+    file: Option<std::fs::File>,
+    done: bool,
+}
+
+impl<'a, S: Sparse + ?Sized> Extents<'a, S> {
+    /// Creates an iterator over the ranges of `io`.
+    pub fn new(io: &'a S) -> Self {
+        Extents {
+            io,
+            pos: 0,
+            size: None,
+            #[cfg(all(feature = "std", any(target_os = "linux", target_os = "android")))]
+            file: None,
+            done: false,
+        }
+    }
+
+    #[cfg(all(feature = "std", any(target_os = "linux", target_os = "android")))]
+    fn open(&mut self) -> io::Result<()> {
+        if let Some(file) = self.io.as_read_file() {
+            self.file = crate::unix::sparse_copy(file)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(all(feature = "std", any(target_os = "linux", target_os = "android"))))]
+    fn open(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        #[cfg(all(feature = "std", any(target_os = "linux", target_os = "android")))]
+        if let Some(file) = &self.file {
+            return crate::unix::seek_sparse(file, pos, libc::SEEK_DATA);
+        }
+        self.io.next_data(pos)
+    }
+
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        #[cfg(all(feature = "std", any(target_os = "linux", target_os = "android")))]
+        if let Some(file) = &self.file {
+            // There are no holes past the end.
+            return Ok(crate::unix::seek_sparse(file, pos, libc::SEEK_HOLE)?.unwrap_or(pos));
+        }
+        self.io.next_hole(pos)
+    }
+
+    fn next_extent(&mut self) -> io::Result<Option<Extent>> {
+        let size = match self.size {
+            Some(size) => size,
+            None => match self.io.size()? {
+                Some(size) => {
+                    self.open()?;
+                    *self.size.insert(size)
+                }
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unknown base size",
+                    ));
+                }
+            },
+        };
+        if self.pos >= size {
+            return Ok(None);
+        }
+
+        let pos = self.pos;
+        let (kind, end) = match self.next_data(pos)? {
+            Some(data) if data > pos => (ExtentKind::Hole, min(data, size)),
+            Some(_) => {
+                // Guard against a hole that doesn't move us forward.
+                let hole = self.next_hole(pos)?;
+                let end = if hole > pos { min(hole, size) } else { size };
+                (ExtentKind::Data, end)
+            }
+            None => (ExtentKind::Hole, size),
+        };
+        self.pos = end;
+        Ok(Some(Extent {
+            kind,
+            pos,
+            len: end - pos,
+        }))
+    }
+}
+
+impl<S: Sparse + ?Sized> Iterator for Extents<'_, S> {
+    type Item = io::Result<Extent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let next = self.next_extent().transpose();
+        if !matches!(next, Some(Ok(_))) {
+            self.done = true;
+        }
+        next
+    }
+}
+
+/// Copies the contents of `src` to `dst`, skipping holes so that the copy
+/// stays sparse.
+///
+/// Only the data ranges of `src` are written, and then the size of `dst` is
+/// set to that of `src`. So `dst` should start out empty, any existing bytes
+/// where `src` has holes are left in place.
+///
+/// Returns the number of bytes of data copied.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// use std::fs::File;
+/// use positioned_io::copy_sparse;
+///
+/// # fn foo() -> io::Result<()> {
+/// let src = File::open("disk.img")?;
+/// let mut dst = File::create("copy.img")?;
+/// copy_sparse(&src, &mut dst)?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub fn copy_sparse<R, W>(src: &R, dst: &mut W) -> io::Result<u64>
+where
+    R: Sparse + ?Sized,
+    W: WriteAt + SetLen + ?Sized,
+{
+    let mut copied = 0;
+    let mut size = 0;
+    for extent in Extents::new(src) {
+        let extent = extent?;
+        if extent.kind == ExtentKind::Data {
+            copied += copy_range(src, extent.pos, dst, extent.pos, extent.len)?;
+        }
+        size = extent.pos + extent.len;
+    }
+    dst.set_len(size)?;
+    Ok(copied)
+}
@@ -0,0 +1,273 @@
+use std::{
+    error, fmt,
+    fs::File,
+    io,
+    io::IoSliceMut,
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+#[cfg(feature = "error-context")]
+use super::PositionedError;
+use super::{Advice, ReadAt, ReadBuf, Size};
+
+/// Detects when the file behind a `ReadAt` is changed while it's being read.
+///
+/// A `StableReader` records the identity and modification metadata of the
+/// underlying file when it's created: the device and inode number, size,
+/// modification and status change times, and on Linux the inode generation.
+/// After reads, the metadata is checked again, and if anything changed the
+/// read fails with a [`ConcurrentModification`](struct.ConcurrentModification.html)
+/// error. The data from that read may be inconsistent, so it should be
+/// thrown away and the scan restarted.
+///
+/// Checking costs an `fstat()` call, so by default it happens after every
+/// read. Use [`check_every()`](#method.check_every) to check less often.
+///
+/// The wrapped object must be backed by a file, as reported by
+/// [`ReadAt::as_read_file()`](trait.ReadAt.html#method.as_read_file).
+/// The checks can only notice changes that update the metadata, and
+/// timestamps have limited resolution on some file systems, so this is a
+/// safeguard against mistakes, not a guarantee.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// use positioned_io::{ConcurrentModification, RandomAccessFile, ReadAt, StableReader};
+///
+/// # fn foo() -> io::Result<()> {
+/// let mut reader = StableReader::new(RandomAccessFile::open("tests/pi.txt")?)?;
+/// let mut buf = [0; 4096];
+/// loop {
+///     match reader.read_exact_at(0, &mut buf) {
+///         Err(e) if ConcurrentModification::find(&e).is_some() => {
+///             // Start over with the new contents.
+///             reader.refresh()?;
+///         }
+///         result => break result,
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StableReader<R> {
+    io: R,
+    snapshot: Snapshot,
+    interval: u64,
+    reads: AtomicU64,
+}
+
+// The metadata that is compared to detect changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Snapshot {
+    id: Option<(u64, u64)>,
+    size: u64,
+    modified: Option<SystemTime>,
+    changed: Option<(i64, i64)>,
+    generation: Option<u64>,
+}
+
+impl Snapshot {
+    fn take(file: &File) -> io::Result<Snapshot> {
+        let meta = file.metadata()?;
+        #[cfg(unix)]
+        let (id, changed) = {
+            use std::os::unix::fs::MetadataExt;
+            (
+                Some((meta.dev(), meta.ino())),
+                Some((meta.ctime(), meta.ctime_nsec())),
+            )
+        };
+        #[cfg(not(unix))]
+        let (id, changed) = (None, None);
+        Ok(Snapshot {
+            id,
+            size: meta.len(),
+            modified: meta.modified().ok(),
+            changed,
+            generation: generation(file),
+        })
+    }
+
+    // Names the first field that differs between two snapshots.
+    fn diff(&self, other: &Snapshot) -> Option<&'static str> {
+        if self.id != other.id {
+            Some("file identity")
+        } else if self.generation != other.generation {
+            Some("generation")
+        } else if self.size != other.size {
+            Some("size")
+        } else if self.modified != other.modified {
+            Some("modification time")
+        } else if self.changed != other.changed {
+            Some("status change time")
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn generation(file: &File) -> Option<u64> {
+    crate::unix::generation(file)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn generation(_file: &File) -> Option<u64> {
+    None
+}
+
+impl<R: ReadAt> StableReader<R> {
+    /// Wraps a file-backed `ReadAt`, recording the current state of the file.
+    ///
+    /// Errors with `ErrorKind::Unsupported` if `io` isn't backed by a file.
+    pub fn new(io: R) -> io::Result<StableReader<R>> {
+        let snapshot = Snapshot::take(file(&io)?)?;
+        Ok(StableReader {
+            io,
+            snapshot,
+            interval: 1,
+            reads: AtomicU64::new(0),
+        })
+    }
+
+    /// Sets how many reads happen between checks, counting from now.
+    ///
+    /// With an interval of `0`, the file is only checked when
+    /// [`check()`](#method.check) is called.
+    pub fn check_every(mut self, interval: u64) -> Self {
+        self.interval = interval;
+        *self.reads.get_mut() = 0;
+        self
+    }
+
+    /// Checks whether the file has changed since this reader was created, or
+    /// since the last [`refresh()`](#method.refresh).
+    ///
+    /// Errors with a [`ConcurrentModification`](struct.ConcurrentModification.html)
+    /// if it has.
+    pub fn check(&self) -> io::Result<()> {
+        let current = Snapshot::take(file(&self.io)?)?;
+        match self.snapshot.diff(&current) {
+            Some(what) => Err(io::Error::other(ConcurrentModification { what })),
+            None => Ok(()),
+        }
+    }
+
+    /// Records the current state of the file, so that reads succeed again
+    /// after a change was detected.
+    pub fn refresh(&mut self) -> io::Result<()> {
+        self.snapshot = Snapshot::take(file(&self.io)?)?;
+        *self.reads.get_mut() = 0;
+        Ok(())
+    }
+
+    // Counts a read, and checks the file if it's time to.
+    fn checked<T>(&self, result: io::Result<T>) -> io::Result<T> {
+        let value = result?;
+        let reads = self.reads.fetch_add(1, Ordering::Relaxed) + 1;
+        if self.interval != 0 && reads % self.interval == 0 {
+            self.check()?;
+        }
+        Ok(value)
+    }
+}
+
+impl<R> StableReader<R> {
+    /// Gets a reference to the underlying value.
+    pub fn get_ref(&self) -> &R {
+        &self.io
+    }
+
+    /// Consumes the reader, returning the underlying value.
+    pub fn into_inner(self) -> R {
+        self.io
+    }
+}
+
+fn file<R: ReadAt>(io: &R) -> io::Result<&File> {
+    io.as_read_file().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "StableReader needs an I/O object backed by a file",
+        )
+    })
+}
+
+/// Reads are checked as a whole, so `read_many_at()` checks once after all
+/// of its requests. The underlying file isn't exposed through
+/// `as_read_file()`, since reading it directly would skip the checks.
+impl<R: ReadAt> ReadAt for StableReader<R> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.checked(self.io.read_at(pos, buf))
+    }
+
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.checked(self.io.read_vectored_at(pos, bufs))
+    }
+
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        let results = self.io.read_many_at(requests);
+        match self.checked(Ok(())) {
+            Ok(()) => results,
+            Err(e) => results
+                .iter()
+                .map(|_| match ConcurrentModification::find(&e) {
+                    Some(modified) => Err(io::Error::other(modified.clone())),
+                    None => Err(e.kind().into()),
+                })
+                .collect(),
+        }
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.checked(self.io.read_buf_at(pos, buf))
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        self.io.advise(pos, len, advice)
+    }
+}
+
+impl<R: Size> Size for StableReader<R> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        self.io.size()
+    }
+}
+
+/// The error returned by a [`StableReader`](struct.StableReader.html) when
+/// the file it reads changes.
+///
+/// It's wrapped in an `io::Error` of kind `ErrorKind::Other`. Use
+/// [`find()`](#method.find) to recognize it.
+#[derive(Debug, Clone)]
+pub struct ConcurrentModification {
+    what: &'static str,
+}
+
+impl ConcurrentModification {
+    /// Finds a `ConcurrentModification` inside an `io::Error`, looking
+    /// through any [`PositionedError`](struct.PositionedError.html) context.
+    pub fn find(err: &io::Error) -> Option<&ConcurrentModification> {
+        let inner = err.get_ref()?;
+        #[cfg(feature = "error-context")]
+        if let Some(context) = inner.downcast_ref::<PositionedError>() {
+            return ConcurrentModification::find(context.get_ref());
+        }
+        inner.downcast_ref()
+    }
+
+    /// Describes which part of the metadata changed, for example `"size"`.
+    pub fn what(&self) -> &'static str {
+        self.what
+    }
+}
+
+impl fmt::Display for ConcurrentModification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "file was modified concurrently ({} changed)", self.what)
+    }
+}
+
+impl error::Error for ConcurrentModification {}
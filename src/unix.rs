@@ -1,20 +1,629 @@
-use std::{fs::File, io, io::Write, os::unix::fs::FileExt};
+use std::{
+    fs::File,
+    io,
+    io::{IoSlice, IoSliceMut, Write},
+    os::unix::fs::FileExt,
+};
 
-use super::{ReadAt, WriteAt};
+use super::{
+    Advice, Allocate, Capabilities, DirectIoAlignment, Durable, Geometry, ReadAt, ReadBuf, Sparse,
+    WriteAt, WriteAtShared,
+};
 
 impl ReadAt for File {
     #[inline]
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         FileExt::read_at(self, buf, pos)
     }
+
+    #[inline]
+    fn read_vectored_at(&self, pos: u64, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        read_vectored_at(self, pos, bufs)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[inline]
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        read_many_at(self, requests)
+    }
+
+    #[inline]
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        read_buf_at(self, pos, buf)
+    }
+
+    #[inline]
+    fn as_read_file(&self) -> Option<&File> {
+        Some(self)
+    }
+
+    #[inline]
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        advise(self, pos, len, advice)
+    }
 }
 
+/// Files opened with `O_APPEND` are rejected with `ErrorKind::InvalidInput` on
+/// Linux, which would otherwise ignore the offset and append. This costs an
+/// extra `fcntl()` call for each write.
 impl WriteAt for File {
     fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
-        FileExt::write_at(self, buf, pos)
+        WriteAtShared::write_at(self, pos, buf)
+    }
+
+    #[inline]
+    fn write_vectored_at(&mut self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        WriteAtShared::write_vectored_at(self, pos, bufs)
+    }
+
+    #[inline]
+    fn as_write_file(&self) -> Option<&File> {
+        Some(self)
     }
 
     fn flush(&mut self) -> io::Result<()> {
         Write::flush(self)
     }
 }
+
+impl WriteAtShared for File {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        check_append(self)?;
+        FileExt::write_at(self, buf, pos)
+    }
+
+    #[inline]
+    fn write_vectored_at(&self, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        check_append(self)?;
+        write_vectored_at(self, pos, bufs)
+    }
+
+    #[inline]
+    fn as_write_file(&self) -> Option<&File> {
+        Some(self)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Write::flush(&mut &*self)
+    }
+}
+
+impl Allocate for File {
+    #[inline]
+    fn preallocate(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        allocate(self, AllocateMode::Preallocate, pos, len)
+    }
+
+    #[inline]
+    fn punch_hole(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        allocate(self, AllocateMode::PunchHole, pos, len)
+    }
+
+    #[inline]
+    fn zero_range(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        allocate(self, AllocateMode::ZeroRange, pos, len)
+    }
+}
+
+impl Durable for File {
+    fn sync_data(&self) -> io::Result<()> {
+        File::sync_data(self)
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        File::sync_all(self)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        // A length of zero would mean "until the end of the file".
+        if len == 0 {
+            return Ok(());
+        }
+        let flags = libc::SYNC_FILE_RANGE_WAIT_BEFORE
+            | libc::SYNC_FILE_RANGE_WRITE
+            | libc::SYNC_FILE_RANGE_WAIT_AFTER;
+        let ret =
+            unsafe { libc::sync_file_range(self.as_raw_fd(), offset(pos)?, offset(len)?, flags) };
+        if ret < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Sparse for File {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        find_sparse(self, pos, libc::SEEK_DATA)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        // There are no holes past the end.
+        Ok(find_sparse(self, pos, libc::SEEK_HOLE)?.unwrap_or(pos))
+    }
+
+    fn allocated_size(&self) -> io::Result<Option<u64>> {
+        use std::os::unix::fs::MetadataExt;
+
+        let md = self.metadata()?;
+        if md.is_file() {
+            // Blocks are always counted in units of 512 bytes.
+            Ok(Some(md.blocks() * 512))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl Geometry for File {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+        let md = self.metadata()?;
+        let block_size = md.blksize().max(1);
+        let (logical_block_size, physical_block_size) = if md.file_type().is_block_device() {
+            sector_sizes(self)?
+        } else {
+            (block_size, block_size)
+        };
+        Ok(Capabilities {
+            logical_block_size,
+            physical_block_size,
+            preferred_io_size: Some(block_size),
+            direct_io_alignment: direct_io_alignment(self)?,
+            extendable: md.is_file(),
+            holes: md.is_file() && cfg!(any(target_os = "linux", target_os = "android")),
+            concurrent_writes: true,
+        })
+    }
+}
+
+/// Checks whether a file was opened with `O_APPEND`.
+///
+/// Linux ignores the offset of `pwrite()` to such files and appends instead,
+/// so positioned writes can't work. Other systems honor the offset, so this
+/// is always false there.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn is_append(file: &File) -> io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    let flags = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETFL) };
+    if flags < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(flags & libc::O_APPEND != 0)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn is_append(_file: &File) -> io::Result<bool> {
+    Ok(false)
+}
+
+pub(crate) fn append_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "can't write at an offset to a file opened for appending",
+    )
+}
+
+/// Fails if a file was opened with `O_APPEND`.
+///
+/// This is checked before every write, rather than once, since the flag can
+/// be changed at any time through another handle to the same open file.
+pub(crate) fn check_append(file: &File) -> io::Result<()> {
+    if is_append(file)? {
+        Err(append_error())
+    } else {
+        Ok(())
+    }
+}
+
+// The kernel rejects more buffers than this in a single call.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IOV_MAX: usize = 1024;
+
+fn offset(pos: u64) -> io::Result<libc::off_t> {
+    libc::off_t::try_from(pos)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset too large"))
+}
+
+/// Reads into several buffers with a single `preadv()`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn read_vectored_at(
+    file: &File,
+    pos: u64,
+    bufs: &mut [IoSliceMut<'_>],
+) -> io::Result<usize> {
+    use std::os::unix::io::AsRawFd;
+
+    let count = bufs.len().min(IOV_MAX);
+    // IoSliceMut is guaranteed to be ABI compatible with iovec.
+    let ret = unsafe {
+        libc::preadv(
+            file.as_raw_fd(),
+            bufs.as_mut_ptr() as *const libc::iovec,
+            count as libc::c_int,
+            offset(pos)?,
+        )
+    };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn read_vectored_at(
+    file: &File,
+    pos: u64,
+    bufs: &mut [IoSliceMut<'_>],
+) -> io::Result<usize> {
+    let buf = bufs
+        .iter_mut()
+        .find(|b| !b.is_empty())
+        .map_or(&mut [][..], |b| &mut **b);
+    FileExt::read_at(file, buf, pos)
+}
+
+/// Writes from several buffers with a single `pwritev()`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn write_vectored_at(file: &File, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+    use std::os::unix::io::AsRawFd;
+
+    let count = bufs.len().min(IOV_MAX);
+    // IoSlice is guaranteed to be ABI compatible with iovec.
+    let ret = unsafe {
+        libc::pwritev(
+            file.as_raw_fd(),
+            bufs.as_ptr() as *const libc::iovec,
+            count as libc::c_int,
+            offset(pos)?,
+        )
+    };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret as usize)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn write_vectored_at(file: &File, pos: u64, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+    let buf = bufs
+        .iter()
+        .find(|b| !b.is_empty())
+        .map_or(&[][..], |b| &**b);
+    FileExt::write_at(file, buf, pos)
+}
+
+/// Reads straight into an uninitialized buffer with `pread()`.
+pub(crate) fn read_buf_at(file: &File, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+    use std::os::unix::io::AsRawFd;
+
+    // Safety: pread() only ever writes initialized bytes.
+    let unfilled = unsafe { buf.unfilled_mut() };
+    let ret = unsafe {
+        libc::pread(
+            file.as_raw_fd(),
+            unfilled.as_mut_ptr() as *mut libc::c_void,
+            unfilled.len(),
+            offset(pos)?,
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Safety: the kernel initialized this many bytes.
+    unsafe { buf.assume_filled(ret as usize) };
+    Ok(ret as usize)
+}
+
+/// Reads many ranges, merging requests that are adjacent in the file into a
+/// single `preadv()`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn read_many_at(
+    file: &File,
+    requests: &mut [(u64, &mut [u8])],
+) -> Vec<io::Result<usize>> {
+    let mut results: Vec<io::Result<usize>> = requests.iter().map(|_| Ok(0)).collect();
+
+    let mut sorted: Vec<(usize, u64, &mut [u8])> = requests
+        .iter_mut()
+        .enumerate()
+        .map(|(i, (pos, buf))| (i, *pos, &mut **buf))
+        .collect();
+    sorted.sort_by_key(|&(_, pos, _)| pos);
+
+    let mut rest = &mut sorted[..];
+    while !rest.is_empty() {
+        // Find a run of requests where each starts right where the last ended.
+        let mut len = 1;
+        let mut next = rest[0].1.checked_add(rest[0].2.len() as u64);
+        while len < rest.len() && len < IOV_MAX && next == Some(rest[len].1) {
+            next = rest[len].1.checked_add(rest[len].2.len() as u64);
+            len += 1;
+        }
+        let (run, tail) = rest.split_at_mut(len);
+        rest = tail;
+
+        let pos = run[0].1;
+        let mut bufs: Vec<IoSliceMut<'_>> = run
+            .iter_mut()
+            .map(|(_, _, buf)| IoSliceMut::new(buf))
+            .collect();
+        match read_vectored_at(file, pos, &mut bufs) {
+            Ok(mut bytes) => {
+                // Hand out the bytes read to each request in turn. A short
+                // read doesn't have to mean the end of the file, so requests
+                // that got nothing are retried individually.
+                for (i, pos, buf) in run.iter_mut() {
+                    if bytes == 0 {
+                        results[*i] = FileExt::read_at(file, buf, *pos);
+                        continue;
+                    }
+                    let n = bytes.min(buf.len());
+                    results[*i] = Ok(n);
+                    bytes -= n;
+                }
+            }
+            Err(_) => {
+                // Retry individually so each request gets its own error.
+                for (i, pos, buf) in run.iter_mut() {
+                    results[*i] = FileExt::read_at(file, buf, *pos);
+                }
+            }
+        }
+    }
+
+    results
+}
+
+/// Passes on advice with `posix_fadvise()`, which doesn't block: ranges that
+/// will be needed are read in the background.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn advise(file: &File, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let fd = file.as_raw_fd();
+    let advice = match advice {
+        Advice::Normal => libc::POSIX_FADV_NORMAL,
+        Advice::Random => libc::POSIX_FADV_RANDOM,
+        Advice::Sequential => libc::POSIX_FADV_SEQUENTIAL,
+        Advice::WillNeed => libc::POSIX_FADV_WILLNEED,
+        Advice::DontNeed => libc::POSIX_FADV_DONTNEED,
+        Advice::NoReuse => libc::POSIX_FADV_NOREUSE,
+    };
+    // posix_fadvise() returns an error number rather than setting errno.
+    match unsafe { libc::posix_fadvise(fd, offset(pos)?, offset(len)?, advice) } {
+        0 => Ok(()),
+        err => Err(io::Error::from_raw_os_error(err)),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn advise(_file: &File, _pos: u64, _len: u64, _advice: Advice) -> io::Result<()> {
+    Ok(())
+}
+
+/// Gets the flags for opening a file with `O_NOATIME`, `O_DIRECT` or
+/// `O_DSYNC`.
+pub(crate) fn open_flags(noatime: bool, direct: bool, dsync: bool) -> io::Result<libc::c_int> {
+    let mut flags = 0;
+    if dsync {
+        flags |= libc::O_DSYNC;
+    }
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        if noatime {
+            flags |= libc::O_NOATIME;
+        }
+        if direct {
+            flags |= libc::O_DIRECT;
+        }
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    if noatime || direct {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "noatime and direct are only supported on Linux",
+        ));
+    }
+    Ok(flags)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum AllocateMode {
+    Preallocate,
+    PunchHole,
+    ZeroRange,
+}
+
+/// Changes the allocation of a range with `fallocate()`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn allocate(file: &File, mode: AllocateMode, pos: u64, len: u64) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // fallocate() rejects empty ranges.
+    if len == 0 {
+        return Ok(());
+    }
+    let mode = match mode {
+        AllocateMode::Preallocate => 0,
+        AllocateMode::PunchHole => libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+        AllocateMode::ZeroRange => libc::FALLOC_FL_ZERO_RANGE,
+    };
+    let ret = unsafe { libc::fallocate(file.as_raw_fd(), mode, offset(pos)?, offset(len)?) };
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn allocate(_file: &File, _mode: AllocateMode, _pos: u64, _len: u64) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "fallocate is not supported on this platform",
+    ))
+}
+
+/// Opens a file again through `/proc/self/fd`, with the same access mode.
+///
+/// The copy is a new open file description, so it has its own file position
+/// and its own open file description locks.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn reopen(file: &File) -> io::Result<File> {
+    use std::os::unix::io::AsRawFd;
+
+    let flags = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETFL) };
+    if flags < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mode = flags & libc::O_ACCMODE;
+    std::fs::OpenOptions::new()
+        .read(mode != libc::O_WRONLY)
+        .write(mode != libc::O_RDONLY)
+        .open(format!("/proc/self/fd/{}", file.as_raw_fd()))
+}
+
+/// Opens a private copy of a file for finding holes with `lseek()`.
+///
+/// `lseek()` moves the file position, which is shared by every handle to the
+/// same open file, so it can't be used on the original. Returns `None` if the
+/// file isn't a regular file, since only those have holes.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn sparse_copy(file: &File) -> io::Result<Option<File>> {
+    if file.metadata()?.is_file() {
+        reopen(file).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Finds the next data or hole with `lseek()` on a private copy from
+/// [`sparse_copy()`], returning `None` if there is none.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn seek_sparse(
+    private: &File,
+    pos: u64,
+    whence: libc::c_int,
+) -> io::Result<Option<u64>> {
+    use std::os::unix::io::AsRawFd;
+
+    let found = unsafe { libc::lseek(private.as_raw_fd(), offset(pos)?, whence) };
+    if found >= 0 {
+        Ok(Some(found as u64))
+    } else {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() == Some(libc::ENXIO) {
+            Ok(None)
+        } else {
+            Err(err)
+        }
+    }
+}
+
+/// Finds the next data or hole in a file, opening a private copy for just
+/// this call.
+///
+/// Files other than regular files are all data.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn find_sparse(file: &File, pos: u64, whence: libc::c_int) -> io::Result<Option<u64>> {
+    if let Some(private) = sparse_copy(file)? {
+        return seek_sparse(&private, pos, whence);
+    }
+    let size = crate::Size::size(file)?;
+    Ok(match (whence, size) {
+        (_, Some(size)) if pos >= size => None,
+        (libc::SEEK_DATA, _) => Some(pos),
+        (_, size) => Some(size.unwrap_or(u64::MAX)),
+    })
+}
+
+/// Gets the inode generation number, if the file system has one.
+#[cfg(target_os = "linux")]
+pub(crate) fn generation(file: &File) -> Option<u64> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut generation: libc::c_int = 0;
+    let ret = unsafe { libc::ioctl(file.as_raw_fd(), libc::FS_IOC_GETVERSION, &mut generation) };
+    (ret == 0).then_some(generation as u32 as u64)
+}
+
+/// Gets the logical and physical sector sizes of a block device.
+#[cfg(target_os = "linux")]
+fn sector_sizes(file: &File) -> io::Result<(u64, u64)> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut logical: libc::c_int = 0;
+    let mut physical: libc::c_uint = 0;
+    unsafe {
+        if libc::ioctl(file.as_raw_fd(), libc::BLKSSZGET, &mut logical) < 0
+            || libc::ioctl(file.as_raw_fd(), libc::BLKPBSZGET, &mut physical) < 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok((logical.max(1) as u64, u64::from(physical.max(1))))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn sector_sizes(file: &File) -> io::Result<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    let block_size = file.metadata()?.blksize().max(1);
+    Ok((block_size, block_size))
+}
+
+/// Gets the alignment needed for `O_DIRECT` with `statx()`.
+///
+/// This is only reported by Linux 6.1 and later, and only for file systems
+/// and devices that support direct I/O.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn direct_io_alignment(file: &File) -> io::Result<Option<DirectIoAlignment>> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut stx: libc::statx = unsafe { std::mem::zeroed() };
+    let ret = unsafe {
+        libc::statx(
+            file.as_raw_fd(),
+            c"".as_ptr(),
+            libc::AT_EMPTY_PATH,
+            libc::STATX_DIOALIGN,
+            &mut stx,
+        )
+    };
+    if ret < 0 {
+        let err = io::Error::last_os_error();
+        // Kernels before 4.11 don't have statx() at all, and seccomp filters
+        // in containers and sandboxes often reject it with another error.
+        // Either way the alignment is just unknown. (`ENOTSUP` is
+        // `EOPNOTSUPP` on Linux.)
+        return match err.raw_os_error() {
+            Some(libc::ENOSYS | libc::EPERM | libc::EACCES | libc::EINVAL | libc::EOPNOTSUPP) => {
+                Ok(None)
+            }
+            _ => Err(err),
+        };
+    }
+    if stx.stx_mask & libc::STATX_DIOALIGN == 0 || stx.stx_dio_offset_align == 0 {
+        return Ok(None);
+    }
+    Ok(Some(DirectIoAlignment {
+        memory: u64::from(stx.stx_dio_mem_align),
+        offset: u64::from(stx.stx_dio_offset_align),
+    }))
+}
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+fn direct_io_alignment(_file: &File) -> io::Result<Option<DirectIoAlignment>> {
+    Ok(None)
+}
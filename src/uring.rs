@@ -0,0 +1,526 @@
+use std::{
+    fmt,
+    fs::File,
+    io,
+    io::Write,
+    os::unix::{fs::FileExt, io::AsRawFd},
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use io_uring::{IoUring, Probe, opcode, squeue, types};
+
+use super::{
+    Advice, Allocate, Capabilities, Durable, Geometry, ReadAt, ReadBuf, SetLen, Size, Sparse,
+    WriteAt, WriteAtShared,
+};
+
+// Default number of submission queue entries.
+const ENTRIES: u32 = 64;
+// Transient submission errors in a row before the ring is given up on.
+const MAX_RETRIES: u32 = 100;
+// User data for cancellation requests, which don't match any result.
+const CANCEL: u64 = u64::MAX;
+// How long to wait for cancelled requests, per attempt.
+const DRAIN_WAIT: Duration = Duration::from_secs(1);
+const DRAIN_ATTEMPTS: u32 = 60;
+
+/// A `File` wrapper that performs positioned I/O through
+/// [io_uring](https://man7.org/linux/man-pages/man7/io_uring.7.html).
+///
+/// The benefit is batching: [`read_many_at()`](trait.ReadAt.html#method.read_many_at)
+/// and [`write_many_at()`](#method.write_many_at) submit all their requests
+/// to a ring at once and wait for them together. Single reads and writes
+/// gain nothing from a ring, so they go straight to `pread()` and `pwrite()`.
+///
+/// Each batch has a ring to itself while it waits. Rings are kept in a pool,
+/// which grows when batches run on several threads at once, so concurrent
+/// batches don't wait for each other.
+///
+/// If the kernel or a sandbox refuses to set up a ring, or a ring fails
+/// later on, `UringFile` quietly falls back to `pread()` and `pwrite()`. Use
+/// [`is_uring()`](#method.is_uring) to find out which is in use. Requests
+/// that are already in flight when a ring fails are cancelled, and return
+/// errors. Their buffers can't be freed while the kernel might still use
+/// them, so if cancelling doesn't finish within a minute, the process is
+/// aborted.
+///
+/// Only available on Linux with the `io-uring` feature.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// use positioned_io::{ReadAt, UringFile};
+///
+/// # fn foo() -> io::Result<()> {
+/// let file = UringFile::open("tests/pi.txt")?;
+///
+/// let mut a = [0; 4];
+/// let mut b = [0; 4];
+/// let results = file.read_many_at(&mut [(10, &mut a[..]), (764, &mut b[..])]);
+/// assert_eq!(&a, b"3589");
+/// assert_eq!(&b, b"9999");
+/// # Ok(())
+/// # }
+/// # fn main() { foo().unwrap(); }
+/// ```
+pub struct UringFile {
+    file: File,
+    // Shared with other files created by share_ring().
+    rings: Arc<Mutex<Rings>>,
+}
+
+// The rings that aren't in use right now.
+struct Rings {
+    entries: u32,
+    idle: Vec<IoUring>,
+    // Set if io_uring is unavailable, or a ring ever ends up in an unknown
+    // state.
+    failed: bool,
+}
+
+impl fmt::Debug for UringFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UringFile")
+            .field("file", &self.file)
+            .field("uring", &self.is_uring())
+            .finish()
+    }
+}
+
+impl UringFile {
+    /// [Opens](https://doc.rust-lang.org/std/fs/struct.File.html#method.open)
+    /// a file for reading through io_uring.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<UringFile> {
+        UringFile::try_new(File::open(path)?)
+    }
+
+    /// Creates a `UringFile` wrapper around a `File`, with a new ring.
+    pub fn try_new(file: File) -> io::Result<UringFile> {
+        UringFile::with_entries(file, ENTRIES)
+    }
+
+    /// Creates a `UringFile` whose rings have room for `entries` requests in
+    /// flight at once.
+    ///
+    /// Larger batches are split up and submitted in turn.
+    pub fn with_entries(file: File, entries: u32) -> io::Result<UringFile> {
+        let ring = new_ring(entries);
+        Ok(UringFile {
+            file,
+            rings: Arc::new(Mutex::new(Rings {
+                entries,
+                failed: ring.is_none(),
+                idle: ring.into_iter().collect(),
+            })),
+        })
+    }
+
+    /// Creates a `UringFile` for another file, which submits through the same
+    /// pool of rings as this one.
+    pub fn share_ring(&self, file: File) -> UringFile {
+        UringFile {
+            file,
+            rings: self.rings.clone(),
+        }
+    }
+
+    /// Returns whether batched requests currently go through io_uring,
+    /// rather than falling back to `pread()` and `pwrite()`.
+    pub fn is_uring(&self) -> bool {
+        !self.lock().failed
+    }
+
+    /// Unwraps the inner `File`.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+
+    /// Writes many independent ranges, returning a result for each request.
+    ///
+    /// This is the counterpart of `read_many_at()`: each result reports how
+    /// many bytes of the corresponding buffer were written, just like
+    /// `write_at()`.
+    pub fn write_many_at(&self, requests: &[(u64, &[u8])]) -> Vec<io::Result<usize>> {
+        // The kernel would append instead of writing at the offsets.
+        match crate::unix::is_append(&self.file) {
+            Ok(false) => {}
+            Ok(true) => {
+                return requests
+                    .iter()
+                    .map(|_| Err(crate::unix::append_error()))
+                    .collect();
+            }
+            Err(e) => {
+                return requests.iter().map(|_| Err(unfinished(&e))).collect();
+            }
+        }
+
+        let fd = types::Fd(self.file.as_raw_fd());
+        let entries: io::Result<Vec<squeue::Entry>> = requests
+            .iter()
+            .enumerate()
+            .map(|(i, (pos, buf))| {
+                let len = buf.len().min(u32::MAX as usize) as u32;
+                Ok(opcode::Write::new(fd, buf.as_ptr(), len)
+                    .offset(offset(*pos)?)
+                    .build()
+                    .user_data(i as u64))
+            })
+            .collect();
+
+        // The buffers stay borrowed until every request has completed.
+        if let Ok(entries) = entries {
+            if let Some(results) = self.run(&entries) {
+                return results;
+            }
+        }
+        requests
+            .iter()
+            .map(|(pos, buf)| FileExt::write_at(&self.file, buf, *pos))
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Rings> {
+        self.rings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Takes an idle ring from the pool, or sets up a new one if they're all
+    // busy.
+    fn take_ring(&self) -> Option<IoUring> {
+        let mut rings = self.lock();
+        if rings.failed {
+            return None;
+        }
+        if let Some(ring) = rings.idle.pop() {
+            return Some(ring);
+        }
+        let entries = rings.entries;
+        drop(rings);
+        new_ring(entries)
+    }
+
+    fn put_ring(&self, ring: IoUring) {
+        let mut rings = self.lock();
+        if !rings.failed {
+            rings.idle.push(ring);
+        }
+    }
+
+    // Stops using io_uring for every file that shares the pool.
+    fn fail(&self) {
+        let mut rings = self.lock();
+        rings.failed = true;
+        rings.idle.clear();
+    }
+
+    /// Submits entries to the ring and waits for all of them to complete.
+    ///
+    /// Returns `None` if the ring is unavailable, and the caller should fall
+    /// back to regular system calls. If the ring fails once requests are in
+    /// flight, they're cancelled, and each request that didn't finish gets an
+    /// error.
+    fn run(&self, entries: &[squeue::Entry]) -> Option<Vec<io::Result<usize>>> {
+        let mut ring = self.take_ring()?;
+        let mut results: Vec<Option<io::Result<usize>>> = entries.iter().map(|_| None).collect();
+
+        let capacity = ring.params().sq_entries() as usize;
+        for chunk in entries.chunks(capacity) {
+            // The queue is always empty between batches, so this can't fail.
+            // Safety: the caller keeps every buffer alive until we return,
+            // and we don't return while requests are still in flight.
+            if unsafe { ring.submission().push_multiple(chunk) }.is_err() {
+                self.fail();
+                return None;
+            }
+            let in_flight = InFlight;
+
+            let mut pending = chunk.len();
+            let mut submitted = false;
+            let mut retries = 0;
+            while pending > 0 {
+                let err = match ring.submit_and_wait(pending) {
+                    Ok(_) => {
+                        submitted = true;
+                        retries = 0;
+                        pending -= reap(&mut ring, &mut results);
+                        continue;
+                    }
+                    Err(e) => e,
+                };
+                if is_transient(&err) && retries < MAX_RETRIES {
+                    retries += 1;
+                    pending -= reap(&mut ring, &mut results);
+                    continue;
+                }
+                if !submitted {
+                    // Nothing was submitted. Drop the ring so the queued
+                    // entries can never reach the kernel.
+                    self.fail();
+                    core::mem::forget(in_flight);
+                    return None;
+                }
+
+                // The ring is in an unknown state, so stop using it. Requests
+                // still in flight must finish before their buffers are freed.
+                self.fail();
+                if !cancel(&mut ring, chunk, &mut results, &mut pending) {
+                    std::process::abort();
+                }
+                core::mem::forget(in_flight);
+                return Some(
+                    results
+                        .into_iter()
+                        .map(|result| result.unwrap_or_else(|| Err(unfinished(&err))))
+                        .collect(),
+                );
+            }
+            core::mem::forget(in_flight);
+        }
+
+        self.put_ring(ring);
+        Some(
+            results
+                .into_iter()
+                .map(|result| result.unwrap_or(Ok(0)))
+                .collect(),
+        )
+    }
+}
+
+// Aborts the process if dropped, which only happens if a panic unwinds while
+// requests are in flight. Otherwise the kernel could write into freed
+// buffers.
+struct InFlight;
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        std::process::abort();
+    }
+}
+
+// Collects completed requests, returning how many there were.
+fn reap(ring: &mut IoUring, results: &mut [Option<io::Result<usize>>]) -> usize {
+    let mut reaped = 0;
+    for cqe in ring.completion() {
+        let Some(slot) = results.get_mut(cqe.user_data() as usize) else {
+            // Cancellation requests have no result of their own.
+            continue;
+        };
+        if slot.is_some() {
+            continue;
+        }
+        let res = cqe.result();
+        *slot = Some(if res < 0 {
+            Err(io::Error::from_raw_os_error(-res))
+        } else {
+            Ok(res as usize)
+        });
+        reaped += 1;
+    }
+    reaped
+}
+
+// Cancels the requests in `chunk` that haven't completed, and waits for the
+// kernel to finish with them. Returns whether they all finished in time.
+fn cancel(
+    ring: &mut IoUring,
+    chunk: &[squeue::Entry],
+    results: &mut [Option<io::Result<usize>>],
+    pending: &mut usize,
+) -> bool {
+    for entry in chunk {
+        let index = entry.get_user_data();
+        if results.get(index as usize).is_some_and(Option::is_none) {
+            let cancel = opcode::AsyncCancel::new(index).build().user_data(CANCEL);
+            // If the queue is full, the request has to finish by itself.
+            // Safety: cancellation requests don't point to any memory.
+            let _ = unsafe { ring.submission().push(&cancel) };
+        }
+    }
+
+    let timeout = types::Timespec::from(DRAIN_WAIT);
+    let args = types::SubmitArgs::new().timespec(&timeout);
+    for _ in 0..DRAIN_ATTEMPTS {
+        if *pending == 0 {
+            return true;
+        }
+        // Without a timeout, this waits for as long as the kernel takes.
+        let _ = if ring.params().is_feature_ext_arg() {
+            ring.submitter().submit_with_args(1, &args)
+        } else {
+            ring.submit_and_wait(1)
+        };
+        *pending -= reap(ring, results);
+    }
+    *pending == 0
+}
+
+// An error for a request that didn't finish, because of `err`.
+fn unfinished(err: &io::Error) -> io::Error {
+    match err.raw_os_error() {
+        Some(code) => io::Error::from_raw_os_error(code),
+        None => io::Error::new(err.kind(), "io_uring request didn't complete"),
+    }
+}
+
+fn new_ring(entries: u32) -> Option<IoUring> {
+    let ring = IoUring::new(entries).ok()?;
+
+    // Older kernels can set up a ring but lack the read and write opcodes.
+    let mut probe = Probe::new();
+    ring.submitter().register_probe(&mut probe).ok()?;
+    if probe.is_supported(opcode::Read::CODE) && probe.is_supported(opcode::Write::CODE) {
+        Some(ring)
+    } else {
+        None
+    }
+}
+
+fn is_transient(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::Interrupted
+        || matches!(e.raw_os_error(), Some(libc::EAGAIN) | Some(libc::EBUSY))
+}
+
+// io_uring treats an offset of -1 as "use the file position".
+fn offset(pos: u64) -> io::Result<u64> {
+    if pos > i64::MAX as u64 {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "offset too large",
+        ))
+    } else {
+        Ok(pos)
+    }
+}
+
+impl ReadAt for UringFile {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        FileExt::read_at(&self.file, buf, pos)
+    }
+
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        let fd = types::Fd(self.file.as_raw_fd());
+        let entries: io::Result<Vec<squeue::Entry>> = requests
+            .iter_mut()
+            .enumerate()
+            .map(|(i, (pos, buf))| {
+                let len = buf.len().min(u32::MAX as usize) as u32;
+                Ok(opcode::Read::new(fd, buf.as_mut_ptr(), len)
+                    .offset(offset(*pos)?)
+                    .build()
+                    .user_data(i as u64))
+            })
+            .collect();
+
+        // The buffers stay borrowed until every request has completed.
+        if let Ok(entries) = entries {
+            if let Some(results) = self.run(&entries) {
+                return results;
+            }
+        }
+        crate::unix::read_many_at(&self.file, requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        crate::unix::read_buf_at(&self.file, pos, buf)
+    }
+
+    fn as_read_file(&self) -> Option<&File> {
+        Some(&self.file)
+    }
+
+    fn advise(&self, pos: u64, len: u64, advice: Advice) -> io::Result<()> {
+        crate::unix::advise(&self.file, pos, len, advice)
+    }
+}
+
+impl WriteAtShared for UringFile {
+    fn write_at(&self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        WriteAtShared::write_at(&self.file, pos, buf)
+    }
+
+    fn as_write_file(&self) -> Option<&File> {
+        Some(&self.file)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        Write::flush(&mut &self.file)
+    }
+}
+
+impl WriteAt for UringFile {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        WriteAtShared::write_at(self, pos, buf)
+    }
+
+    fn as_write_file(&self) -> Option<&File> {
+        Some(&self.file)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        WriteAtShared::flush(self)
+    }
+}
+
+impl Size for UringFile {
+    fn size(&self) -> io::Result<Option<u64>> {
+        self.file.size()
+    }
+}
+
+impl SetLen for UringFile {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        self.file.set_len(len)
+    }
+}
+
+impl Allocate for UringFile {
+    fn preallocate(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        self.file.preallocate(pos, len)
+    }
+
+    fn punch_hole(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        self.file.punch_hole(pos, len)
+    }
+
+    fn zero_range(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        self.file.zero_range(pos, len)
+    }
+}
+
+impl Durable for UringFile {
+    fn sync_data(&self) -> io::Result<()> {
+        self.file.sync_data()
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        self.file.sync_all()
+    }
+
+    fn sync_range(&self, pos: u64, len: u64) -> io::Result<()> {
+        self.file.sync_range(pos, len)
+    }
+}
+
+impl Geometry for UringFile {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        self.file.geometry()
+    }
+}
+
+impl Sparse for UringFile {
+    fn next_data(&self, pos: u64) -> io::Result<Option<u64>> {
+        self.file.next_data(pos)
+    }
+
+    fn next_hole(&self, pos: u64) -> io::Result<u64> {
+        self.file.next_hole(pos)
+    }
+
+    fn allocated_size(&self) -> io::Result<Option<u64>> {
+        self.file.allocated_size()
+    }
+}
@@ -1,23 +1,40 @@
-use std::{cmp::min, io};
+use alloc::{borrow::Cow, collections::VecDeque, vec::Vec};
+use core::cmp::min;
 
-use super::{ReadAt, Size, WriteAt};
+use super::{
+    Allocate, Capabilities, Durable, Geometry, ReadAt, ReadAtRef, ReadBuf, SetLen, Size, Sparse,
+    WriteAt, io,
+};
 
 impl ReadAt for Vec<u8> {
     fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
         self.as_slice().read_at(pos, buf)
     }
+
+    fn read_many_at(&self, requests: &mut [(u64, &mut [u8])]) -> Vec<io::Result<usize>> {
+        self.as_slice().read_many_at(requests)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        self.as_slice().read_buf_at(pos, buf)
+    }
+
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+impl ReadAtRef for Vec<u8> {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(crate::array::borrow_at(self, pos, len))
+    }
 }
 
 impl WriteAt for Vec<u8> {
     fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
         // Ensure no overflow.
-        if pos > (usize::MAX as u64) {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "vector size too big",
-            ));
-        }
-        let pos = pos as usize;
+        let (pos, end) = range(pos, buf.len() as u64)?;
+        reserve(self, end)?;
 
         // Resize the vector so pos <= self.len().
         if pos >= self.len() {
@@ -48,3 +65,211 @@ impl Size for Vec<u8> {
         Ok(Some(self.len() as u64))
     }
 }
+
+impl Geometry for Vec<u8> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        Ok(Capabilities::memory(true))
+    }
+}
+
+impl Sparse for Vec<u8> {}
+
+impl Durable for Vec<u8> {
+    fn sync_data(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn sync_range(&self, _pos: u64, _len: u64) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SetLen for Vec<u8> {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        // Ensure no overflow.
+        let (_, len) = range(0, len)?;
+        reserve(self, len)?;
+        self.resize(len, 0);
+        Ok(())
+    }
+}
+
+/// A `Cow` is read from whatever it holds, and written like a `Vec`. The first
+/// write copies borrowed data into a new vector.
+impl ReadAt for Cow<'_, [u8]> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let immutable: &[u8] = self;
+        immutable.read_at(pos, buf)
+    }
+
+    fn read_buf_at(&self, pos: u64, buf: &mut ReadBuf<'_>) -> io::Result<usize> {
+        let immutable: &[u8] = self;
+        immutable.read_buf_at(pos, buf)
+    }
+
+    fn read_cow_at(&self, pos: u64, len: usize) -> io::Result<Cow<'_, [u8]>> {
+        Ok(Cow::Borrowed(self.read_ref_at(pos, len)?))
+    }
+}
+
+impl ReadAtRef for Cow<'_, [u8]> {
+    fn read_ref_at(&self, pos: u64, len: usize) -> io::Result<&[u8]> {
+        Ok(crate::array::borrow_at(self, pos, len))
+    }
+}
+
+impl WriteAt for Cow<'_, [u8]> {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        self.to_mut().write_at(pos, buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Size for Cow<'_, [u8]> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        Ok(Some(self.len() as u64))
+    }
+}
+
+impl Geometry for Cow<'_, [u8]> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        Ok(Capabilities::memory(true))
+    }
+}
+
+impl SetLen for Cow<'_, [u8]> {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        SetLen::set_len(self.to_mut(), len)
+    }
+}
+
+/// A `VecDeque` is written like a `Vec`, growing as needed. Its contents may
+/// be split in two internally, so a single read or write can cover both
+/// halves.
+impl ReadAt for VecDeque<u8> {
+    fn read_at(&self, pos: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let (front, back) = self.as_slices();
+        let mut bytes = front.read_at(pos, buf)?;
+        let back_pos = pos
+            .saturating_add(bytes as u64)
+            .saturating_sub(front.len() as u64);
+        bytes += back.read_at(back_pos, &mut buf[bytes..])?;
+        Ok(bytes)
+    }
+}
+
+impl WriteAt for VecDeque<u8> {
+    fn write_at(&mut self, pos: u64, buf: &[u8]) -> io::Result<usize> {
+        // Ensure no overflow.
+        let (pos, end) = range(pos, buf.len() as u64)?;
+        self.try_reserve(end.saturating_sub(self.len()))
+            .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "vector allocation failed"))?;
+
+        // Resize the deque so pos <= self.len().
+        if pos >= self.len() {
+            self.resize(pos, 0);
+        }
+
+        // Copy anything that fits into existing space, which may span both
+        // halves.
+        let avail = min(self.len() - pos, buf.len());
+        let (front, back) = self.as_mut_slices();
+        let in_front = front.len().saturating_sub(pos);
+        if in_front > 0 {
+            let bytes = min(in_front, avail);
+            front[pos..(pos + bytes)].copy_from_slice(&buf[..bytes]);
+        }
+        if avail > in_front {
+            let back_pos = pos.saturating_sub(front.len());
+            let bytes = avail - in_front;
+            back[back_pos..(back_pos + bytes)].copy_from_slice(&buf[in_front..avail]);
+        }
+
+        // Extend with anything leftover.
+        if avail < buf.len() {
+            self.extend(&buf[avail..]);
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Size for VecDeque<u8> {
+    fn size(&self) -> io::Result<Option<u64>> {
+        Ok(Some(self.len() as u64))
+    }
+}
+
+impl Geometry for VecDeque<u8> {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        Ok(Capabilities::memory(true))
+    }
+}
+
+impl SetLen for VecDeque<u8> {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        // Ensure no overflow.
+        let (_, len) = range(0, len)?;
+        self.try_reserve(len.saturating_sub(self.len()))
+            .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "vector allocation failed"))?;
+        self.resize(len, 0);
+        Ok(())
+    }
+}
+
+// Vectors can't hold more bytes than this.
+const MAX_LEN: u64 = isize::MAX as u64;
+
+// Make room for a vector to grow to len bytes, failing rather than aborting if
+// memory can't be allocated.
+pub(crate) fn reserve(vec: &mut Vec<u8>, len: usize) -> io::Result<()> {
+    vec.try_reserve(len.saturating_sub(vec.len()))
+        .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, "vector allocation failed"))
+}
+
+// Get the bounds of a range, ensuring no overflow.
+fn range(pos: u64, len: u64) -> io::Result<(usize, usize)> {
+    match pos.checked_add(len) {
+        Some(end) if end <= MAX_LEN => Ok((pos as usize, end as usize)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "vector size too big",
+        )),
+    }
+}
+
+impl Allocate for Vec<u8> {
+    fn preallocate(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        let (_, end) = range(pos, len)?;
+        if end > self.len() {
+            reserve(self, end)?;
+            self.resize(end, 0);
+        }
+        Ok(())
+    }
+
+    fn punch_hole(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        let (start, end) = range(pos, len)?;
+        let end = min(end, self.len());
+        if start < end {
+            self[start..end].fill(0);
+        }
+        Ok(())
+    }
+
+    fn zero_range(&mut self, pos: u64, len: u64) -> io::Result<()> {
+        self.preallocate(pos, len)?;
+        self.punch_hole(pos, len)
+    }
+}
@@ -17,12 +17,12 @@ use self::winapi::{
     },
     um::{
         handleapi::CloseHandle,
-        memoryapi::{CreateFileMappingW, MapViewOfFile, UnmapViewOfFile, FILE_MAP_READ},
+        memoryapi::{CreateFileMappingW, FILE_MAP_READ, MapViewOfFile, UnmapViewOfFile},
         sysinfoapi::GetSystemInfo,
         winnt::{HANDLE, PAGE_READONLY},
     },
 };
-use super::{ReadAt, WriteAt};
+use super::{Allocate, Capabilities, Durable, Geometry, ReadAt, Sparse, WriteAt};
 
 fn result(e: BOOL) -> io::Result<()> {
     if e == 0 {
@@ -90,6 +90,10 @@ impl ReadAt for File {
 
         Ok(len)
     }
+
+    fn as_read_file(&self) -> Option<&File> {
+        Some(self)
+    }
 }
 
 impl WriteAt for File {
@@ -100,7 +104,62 @@ impl WriteAt for File {
         Ok(result)
     }
 
+    fn as_write_file(&self) -> Option<&File> {
+        Some(self)
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         Write::flush(self)
     }
 }
+
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "fallocate is not supported on this platform",
+    )
+}
+
+impl Allocate for File {
+    fn preallocate(&mut self, _pos: u64, _len: u64) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    fn punch_hole(&mut self, _pos: u64, _len: u64) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    fn zero_range(&mut self, _pos: u64, _len: u64) -> io::Result<()> {
+        Err(unsupported())
+    }
+}
+
+impl Durable for File {
+    fn sync_data(&self) -> io::Result<()> {
+        File::sync_data(self)
+    }
+
+    fn sync_all(&self) -> io::Result<()> {
+        File::sync_all(self)
+    }
+}
+
+impl Sparse for File {}
+
+impl Geometry for File {
+    fn geometry(&self) -> io::Result<Capabilities> {
+        // Sector sizes aren't queried, so assume the size of a page.
+        let is_file = self.metadata()?.is_file();
+        Ok(Capabilities {
+            logical_block_size: 4096,
+            physical_block_size: 4096,
+            preferred_io_size: Some(4096),
+            direct_io_alignment: None,
+            extendable: is_file,
+            holes: false,
+            // Positioned writes move the file cursor, so files aren't
+            // `WriteAtShared` here. `RandomAccessFile` is.
+            concurrent_writes: false,
+        })
+    }
+}
@@ -0,0 +1,91 @@
+// Tests that only use what's available without the `std` feature, so they
+// also run with `--no-default-features`.
+
+extern crate positioned_io;
+
+use positioned_io::{
+    Error, ErrorKind, ReadAt, ReadAtExt, ReadAtRef, ReadBuf, Size, Slice, WriteAt,
+};
+
+#[test]
+fn test_error() {
+    let err = Error::new(ErrorKind::InvalidData, "bad header");
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), "bad header");
+    assert_eq!(
+        Error::from(ErrorKind::Unsupported).kind(),
+        ErrorKind::Unsupported
+    );
+}
+
+#[test]
+fn test_byte_slices() {
+    let data = [1u8, 2, 3, 4, 5];
+    let io = &data[..];
+    assert_eq!(io.size().unwrap(), Some(5));
+    let mut buf = [0; 2];
+    io.read_exact_at(3, &mut buf).unwrap();
+    assert_eq!(buf, [4, 5]);
+    let err = io.read_exact_at(4, &mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(io.read_at_most(4, &mut buf).unwrap(), 1);
+    assert_eq!(io.read_array_at::<3>(1).unwrap(), [2, 3, 4]);
+    assert_eq!(io.read_ref_at(1, 2).unwrap(), &[2, 3]);
+
+    let mut storage = [0u8; 4];
+    let mut io = &mut storage[..];
+    io.write_all_at(1, &[7, 8]).unwrap();
+    let err = io.write_all_at(3, &[9, 9]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+    assert_eq!(storage, [0, 7, 8, 9]);
+
+    let mut storage = [std::mem::MaybeUninit::uninit(); 8];
+    let mut buf = ReadBuf::new(&mut storage);
+    assert_eq!(data.read_buf_at(2, &mut buf).unwrap(), 3);
+    assert_eq!(buf.filled(), &[3, 4, 5]);
+}
+
+#[test]
+fn test_slice() {
+    let data = [1u8, 2, 3, 4, 5];
+    let slice = Slice::new(&data[..], 1, Some(3));
+    assert_eq!(slice.size().unwrap(), Some(3));
+    let mut buf = [0; 4];
+    assert_eq!(slice.read_at(0, &mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], &[2, 3, 4]);
+    assert_eq!(slice.read_at(3, &mut buf).unwrap(), 0);
+
+    let mut storage = [0u8; 5];
+    let mut slice = Slice::new(&mut storage[..], 2, Some(2));
+    assert_eq!(slice.write_at(1, &[6, 6, 6]).unwrap(), 1);
+    assert_eq!(storage, [0, 0, 0, 6, 0]);
+
+    let slice = Slice::new(&data[..], u64::MAX, None);
+    let err = slice.read_at(1, &mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_alloc() {
+    use positioned_io::{Allocate, SetLen};
+    use std::sync::Arc;
+
+    let mut v = Vec::new();
+    v.write_all_at(2, &[1, 2]).unwrap();
+    assert_eq!(v, [0, 0, 1, 2]);
+    SetLen::set_len(&mut v, 3).unwrap();
+    assert_eq!(v, [0, 0, 1]);
+    assert_eq!(v.read_vec_at(1, 2).unwrap(), [0, 1]);
+    let mut end = Vec::new();
+    assert_eq!(v.read_to_end_at(1, &mut end).unwrap(), 2);
+    assert_eq!(end, [0, 1]);
+    let err = v.preallocate(0, u64::MAX >> 2).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+
+    let shared: Arc<[u8]> = Arc::from(&[5u8, 6, 7][..]);
+    assert_eq!(shared.size().unwrap(), Some(3));
+    assert_eq!(shared.read_ref_at(1, 5).unwrap(), &[6, 7]);
+    let boxed: Box<[u8]> = Box::from(&[5u8, 6, 7][..]);
+    assert_eq!(boxed.read_vec_at(0, 3).unwrap(), [5, 6, 7]);
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "std")]
+
 extern crate positioned_io;
 #[macro_use]
 extern crate quickcheck;
@@ -51,7 +53,7 @@ impl Model {
     fn write_all(&mut self, buf: &[u8]) {
         let len = max(self.vec.len(), self.pos + buf.len());
         self.vec.resize(len, 0);
-        self.vec[self.pos..(self.pos + buf.len())].copy_from_slice(&buf);
+        self.vec[self.pos..(self.pos + buf.len())].copy_from_slice(buf);
         self.pos += buf.len();
     }
 
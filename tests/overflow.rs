@@ -0,0 +1,230 @@
+#![cfg(feature = "std")]
+
+extern crate positioned_io;
+#[macro_use]
+extern crate quickcheck;
+
+use std::{
+    cmp::min,
+    io::{ErrorKind, Read, Result, Seek, SeekFrom, Write},
+};
+
+use positioned_io::{Cursor, ReadAt, SizeCursor, Slice, WriteAt, copy_range};
+
+use self::quickcheck::{Arbitrary, Gen, TestResult};
+
+// A position that is often close to an overflow boundary.
+#[derive(Clone, Copy, Debug)]
+struct Pos(u64);
+
+impl Arbitrary for Pos {
+    fn arbitrary(g: &mut Gen) -> Pos {
+        let n = u64::arbitrary(g) % 32;
+        Pos(match u8::arbitrary(g) % 4 {
+            0 => n,
+            1 => u64::MAX - n,
+            2 => i64::MAX as u64 - 16 + n,
+            _ => u64::arbitrary(g),
+        })
+    }
+}
+
+// An offset that is often close to the limits of i64.
+#[derive(Clone, Copy, Debug)]
+struct Offset(i64);
+
+impl Arbitrary for Offset {
+    fn arbitrary(g: &mut Gen) -> Offset {
+        let n = i64::arbitrary(g) % 32;
+        Offset(match u8::arbitrary(g) % 4 {
+            0 => n,
+            1 => i64::MAX - n.abs(),
+            2 => i64::MIN + n.abs(),
+            _ => i64::arbitrary(g),
+        })
+    }
+}
+
+fn avail(size: Option<Pos>, pos: u64, len: usize) -> usize {
+    match size {
+        None => len,
+        Some(Pos(size)) if pos >= size => 0,
+        Some(Pos(size)) => min(len as u64, size - pos) as usize,
+    }
+}
+
+fn same_result(a: &Result<usize>, b: &Result<usize>) -> bool {
+    match (a, b) {
+        (Ok(a), Ok(b)) => a == b,
+        (Err(a), Err(b)) => a.kind() == b.kind(),
+        _ => false,
+    }
+}
+
+fn invalid_input(result: &Result<u64>) -> bool {
+    matches!(result, Err(e) if e.kind() == ErrorKind::InvalidInput)
+}
+
+// Reads and writes one byte at a time, at any position.
+struct Endless;
+
+impl ReadAt for Endless {
+    fn read_at(&self, _pos: u64, buf: &mut [u8]) -> Result<usize> {
+        Ok(min(buf.len(), 1))
+    }
+}
+
+impl WriteAt for Endless {
+    fn write_at(&mut self, _pos: u64, buf: &[u8]) -> Result<usize> {
+        Ok(min(buf.len(), 1))
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn loop_result(result: Result<()>, pos: u64, len: usize) -> bool {
+    match result {
+        Ok(()) => pos.checked_add(len as u64).is_some(),
+        Err(e) => e.kind() == ErrorKind::InvalidInput && pos.checked_add(len as u64).is_none(),
+    }
+}
+
+quickcheck! {
+    fn slice_read_never_wraps(offset: Pos, size: Option<Pos>, pos: Pos, len: u8) -> bool {
+        let data: Vec<u8> = (0..64).collect();
+        let slice = Slice::new(&data, offset.0, size.map(|s| s.0));
+        let mut buf = vec![0; len as usize];
+        let result = slice.read_at(pos.0, &mut buf);
+
+        let bytes = avail(size, pos.0, buf.len());
+        let mut expected_buf = vec![0; len as usize];
+        let expected = if bytes == 0 {
+            Ok(0)
+        } else {
+            match pos.0.checked_add(offset.0) {
+                Some(inner) => data.read_at(inner, &mut expected_buf[..bytes]),
+                None => {
+                    return matches!(result, Err(ref e) if e.kind() == ErrorKind::InvalidInput);
+                }
+            }
+        };
+        same_result(&result, &expected) && buf == expected_buf
+    }
+
+    fn slice_write_never_wraps(offset: Pos, size: Option<Pos>, pos: Pos, buf: Vec<u8>) -> bool {
+        let mut data = [1; 8];
+        let mut expected_data = data;
+        let result = Slice::new(&mut data[..], offset.0, size.map(|s| s.0)).write_at(pos.0, &buf);
+
+        let bytes = avail(size, pos.0, buf.len());
+        let expected = if bytes == 0 {
+            Ok(0)
+        } else {
+            match pos.0.checked_add(offset.0) {
+                Some(inner) => (&mut expected_data[..]).write_at(inner, &buf[..bytes]),
+                None => {
+                    return matches!(result, Err(ref e) if e.kind() == ErrorKind::InvalidInput);
+                }
+            }
+        };
+        same_result(&result, &expected) && data == expected_data
+    }
+
+    fn slice_to_end_checks_offset(len: u8, offset: Pos) -> bool {
+        let data = vec![0; len as usize];
+        match Slice::new_to_end(&data, offset.0) {
+            Ok(slice) => {
+                offset.0 <= len as u64
+                    && slice.read_at(0, &mut [0; 1]).unwrap() == min(1, len as u64 - offset.0) as usize
+            }
+            Err(e) => offset.0 > len as u64 && e.kind() == ErrorKind::InvalidInput,
+        }
+    }
+
+    fn cursor_seek_never_wraps(start: Pos, offset: Offset) -> bool {
+        let mut cursor = Cursor::new_pos(Vec::<u8>::new(), start.0);
+        let result = cursor.seek(SeekFrom::Current(offset.0));
+        match start.0.checked_add_signed(offset.0) {
+            Some(pos) => result.ok() == Some(pos) && cursor.position() == pos,
+            None => invalid_input(&result) && cursor.position() == start.0,
+        }
+    }
+
+    fn size_cursor_seek_never_wraps(size: Pos, start: Pos, offset: Offset, from_end: bool) -> bool {
+        // A slice can claim any size, without allocating.
+        let io = Slice::new(Vec::<u8>::new(), 0, Some(size.0));
+        let mut cursor = SizeCursor::new_pos(io, start.0);
+        let (base, whence) = if from_end {
+            (size.0, SeekFrom::End(offset.0))
+        } else {
+            (start.0, SeekFrom::Current(offset.0))
+        };
+        let result = cursor.seek(whence);
+        match base.checked_add_signed(offset.0) {
+            Some(pos) => result.ok() == Some(pos) && cursor.position() == pos,
+            None => invalid_input(&result) && cursor.position() == start.0,
+        }
+    }
+
+    fn vec_write_never_wraps(pos: Pos, buf: Vec<u8>) -> TestResult {
+        // Positions that could really be allocated are covered elsewhere.
+        if (64..1 << 56).contains(&pos.0) {
+            return TestResult::discard();
+        }
+        let mut data = Vec::new();
+        let end = pos.0.checked_add(buf.len() as u64);
+        TestResult::from_bool(match data.write_at(pos.0, &buf) {
+            Ok(n) => n == buf.len() && pos.0 < 64,
+            Err(e) if e.kind() == ErrorKind::InvalidInput => {
+                end.is_none_or(|end| end > isize::MAX as u64)
+            }
+            Err(e) => e.kind() == ErrorKind::OutOfMemory && data.is_empty(),
+        })
+    }
+
+    fn read_exact_never_wraps(pos: Pos, len: u8) -> bool {
+        let mut buf = vec![0; len as usize];
+        loop_result(Endless.read_exact_at(pos.0, &mut buf), pos.0, buf.len())
+    }
+
+    fn write_all_never_wraps(pos: Pos, len: u8) -> bool {
+        let buf = vec![0; len as usize];
+        loop_result(Endless.write_all_at(pos.0, &buf), pos.0, buf.len())
+    }
+
+    fn cursor_read_checks_first(pos: Pos, len: u8) -> bool {
+        let mut cursor = Cursor::new_pos(Endless, pos.0);
+        let mut buf = vec![0; len as usize];
+        match cursor.read(&mut buf) {
+            Ok(n) => pos.0.checked_add(len as u64).is_some() && cursor.position() == pos.0 + n as u64,
+            Err(e) => {
+                e.kind() == ErrorKind::InvalidInput
+                    && pos.0.checked_add(len as u64).is_none()
+                    && cursor.position() == pos.0
+            }
+        }
+    }
+
+    fn cursor_write_checks_first(pos: Pos, len: u8) -> bool {
+        let mut cursor = Cursor::new_pos(Endless, pos.0);
+        match cursor.write(&vec![0; len as usize]) {
+            Ok(n) => pos.0.checked_add(len as u64).is_some() && cursor.position() == pos.0 + n as u64,
+            Err(e) => {
+                e.kind() == ErrorKind::InvalidInput
+                    && pos.0.checked_add(len as u64).is_none()
+                    && cursor.position() == pos.0
+            }
+        }
+    }
+
+    fn copy_never_wraps(src_pos: Pos, dst_pos: Pos, len: u8) -> bool {
+        let fits = src_pos.0.checked_add(len as u64).is_some()
+            && dst_pos.0.checked_add(len as u64).is_some();
+        match copy_range(&Endless, src_pos.0, &mut Endless, dst_pos.0, len as u64) {
+            Ok(n) => fits && n == len as u64,
+            Err(e) => e.kind() == ErrorKind::InvalidInput && !fits,
+        }
+    }
+}
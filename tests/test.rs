@@ -1,7 +1,10 @@
+#![cfg(feature = "std")]
+
 use std::{
+    borrow::Cow,
     cell::{Cell, RefCell},
     fs::File,
-    io::{Error, ErrorKind, Read, Result, Seek, SeekFrom},
+    io::{Error, ErrorKind, IoSlice, IoSliceMut, Read, Result, Seek, SeekFrom, Write},
     str,
     sync::Arc,
 };
@@ -12,7 +15,12 @@ extern crate positioned_io;
 extern crate tempfile;
 #[cfg(feature = "byteorder")]
 use positioned_io::ByteIo;
-use positioned_io::{Cursor, RandomAccessFile, ReadAt, Size, SizeCursor, Slice, WriteAt};
+use positioned_io::{
+    Advice, Allocate, AnyIo, ConcurrentModification, Cursor, Durable, ErrorType, Extent,
+    ExtentKind, Geometry, IoAdapter, RandomAccessFile, ReadAt, ReadAtExt, ReadAtRef, ReadBuf,
+    SetLen, Size, SizeCursor, Slice, Sparse, StableReader, TryAdapter, TryReadAt, TryWriteAt,
+    WriteAt, copy_range, copy_sparse,
+};
 
 #[cfg(feature = "byteorder")]
 use self::byteorder::LittleEndian;
@@ -46,7 +54,7 @@ struct ReadCustom<I: ReadAt, F: Fn() -> Result<usize>> {
 impl<I: ReadAt, F: Fn() -> Result<usize>> ReadCustom<I, F> {
     fn new(i: I, f: F) -> Self {
         ReadCustom {
-            i: i,
+            i,
             fail: Cell::new(true),
             onfail: f,
         }
@@ -79,7 +87,7 @@ fn test_read_fails() {
 
     // Test errors.
     {
-        let fail = ReadCustom::new(&file, || Err(Error::new(ErrorKind::Other, "random fail")));
+        let fail = ReadCustom::new(&file, || Err(Error::other("random fail")));
         assert!(fail.read_exact_at(10, buf.as_mut()).is_err());
     }
 
@@ -149,7 +157,7 @@ fn test_vector() {
     // Write past the end.
     let mut v = vec![0, 1, 2, 3];
     let buf = [4, 5, 6, 7];
-    (&mut v).write_all_at(2, &buf).unwrap();
+    v.write_all_at(2, &buf).unwrap();
     assert_eq!(vec![0, 1, 4, 5, 6, 7], v);
 }
 
@@ -285,6 +293,1379 @@ fn shared_refs() {
     let file = RandomAccessFile::try_new(file).unwrap();
     (&file).write_at(1, &[1, 2, 3, 4]).unwrap();
     let mut buf = [0; 3];
-    (&file).read_exact_at(0, &mut buf[..]).unwrap();
+    file.read_exact_at(0, &mut buf[..]).unwrap();
     assert_eq!(buf, [0, 1, 2])
 }
+
+#[test]
+fn test_vectored() {
+    // Native implementation.
+    let file = RandomAccessFile::open("tests/pi.txt").unwrap();
+    let mut a = [0; 4];
+    let mut b = [0; 4];
+    let bytes = file
+        .read_vectored_at(10, &mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+        .unwrap();
+    assert_eq!(bytes, 8);
+    assert_eq!(&a, b"3589");
+    assert_eq!(&b, b"7932");
+
+    // Default implementation only fills the first non-empty buffer.
+    let v = vec![0, 1, 2, 3, 4, 5];
+    let mut a = [9; 2];
+    let mut b = [9; 2];
+    let bytes = v
+        .read_vectored_at(
+            1,
+            &mut [
+                IoSliceMut::new(&mut []),
+                IoSliceMut::new(&mut a),
+                IoSliceMut::new(&mut b),
+            ],
+        )
+        .unwrap();
+    assert_eq!(bytes, 2);
+    assert_eq!(a, [1, 2]);
+    assert_eq!(b, [9, 9]);
+
+    // Slices trim the buffers to their size.
+    let file = tempfile::tempfile().unwrap();
+    let file = RandomAccessFile::try_new(file).unwrap();
+    {
+        let mut slice = Slice::new(&file, 2, Some(5));
+        let bytes = slice
+            .write_vectored_at(1, &[IoSlice::new(&[1, 2, 3]), IoSlice::new(&[4, 5, 6])])
+            .unwrap();
+        assert_eq!(bytes, 4);
+
+        let mut a = [9; 3];
+        let mut b = [9; 3];
+        let bytes = slice
+            .read_vectored_at(0, &mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+            .unwrap();
+        assert_eq!(bytes, 5);
+        assert_eq!(a, [0, 1, 2]);
+        assert_eq!(b, [3, 4, 9]);
+    }
+
+    // Cursors advance by the total.
+    let mut curs = Cursor::new(Vec::new());
+    curs.write_all(&[7; 3]).unwrap();
+    let bytes = curs
+        .write_vectored(&[IoSlice::new(&[1, 2]), IoSlice::new(&[3])])
+        .unwrap();
+    assert!(bytes > 0);
+    assert_eq!(curs.position(), 3 + bytes as u64);
+}
+
+#[test]
+fn test_read_many() {
+    let file = RandomAccessFile::open("tests/pi.txt").unwrap();
+    let mut a = [0; 4];
+    let mut b = [0; 4];
+    let mut c = [0; 2];
+    let mut d = [0; 4];
+    let mut e = [0; 4];
+    // Out of order, with adjacent ranges and one past the end.
+    let results = file.read_many_at(&mut [
+        (14, &mut a[..]),
+        (1000000, &mut e[..]),
+        (10, &mut b[..]),
+        (764, &mut c[..]),
+        (2000000, &mut d[..]),
+    ]);
+    let results: Vec<usize> = results.into_iter().map(|r| r.unwrap()).collect();
+    assert_eq!(results, vec![4, 2, 4, 2, 0]);
+    assert_eq!(&a, b"7932");
+    assert_eq!(&b, b"3589");
+    assert_eq!(&c, b"99");
+    assert_eq!(&e[..2], b"51");
+
+    // Slices translate and limit each request.
+    let slice = Slice::new(&file, 10, Some(6));
+    let mut a = [0; 4];
+    let mut b = [0; 4];
+    let results = slice.read_many_at(&mut [(4, &mut a[..]), (0, &mut b[..])]);
+    let results: Vec<usize> = results.into_iter().map(|r| r.unwrap()).collect();
+    assert_eq!(results, vec![2, 4]);
+    assert_eq!(&a[..2], b"79");
+    assert_eq!(&b, b"3589");
+}
+
+#[test]
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+fn test_uring() {
+    use positioned_io::UringFile;
+
+    let file = UringFile::with_entries(tempfile::tempfile().unwrap(), 2).unwrap();
+    // More requests than fit in the ring at once.
+    let results = file.write_many_at(&[(4, b"efgh"), (0, b"abcd"), (12, b"mn"), (10, b"kl")]);
+    for result in results {
+        assert!(result.unwrap() > 0);
+    }
+    (&file).write_all_at(8, b"ij").unwrap();
+    assert_eq!(file.size().unwrap(), Some(14));
+
+    let mut a = [0; 4];
+    let mut b = [0; 6];
+    let mut c = [0; 4];
+    let results = file.read_many_at(&mut [(10, &mut a[..]), (0, &mut b[..]), (14, &mut c[..])]);
+    let results: Vec<usize> = results.into_iter().map(|r| r.unwrap()).collect();
+    assert_eq!(results, vec![4, 6, 0]);
+    assert_eq!(&a, b"klmn");
+    assert_eq!(&b, b"abcdef");
+
+    // Files can share a ring.
+    let other = file.share_ring(File::open("tests/pi.txt").unwrap());
+    assert_eq!(other.is_uring(), file.is_uring());
+    let mut buf = [0; 4];
+    other.read_exact_at(10, &mut buf).unwrap();
+    assert_eq!(&buf, b"3589");
+
+    // Batches on several threads at once each get a ring.
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..100 {
+                    let mut a = [0; 4];
+                    let mut b = [0; 2];
+                    let results = other.read_many_at(&mut [(10, &mut a[..]), (764, &mut b[..])]);
+                    let results: Vec<usize> = results.into_iter().map(|r| r.unwrap()).collect();
+                    assert_eq!(results, vec![4, 2]);
+                    assert_eq!((&a, &b), (b"3589", b"99"));
+                }
+            });
+        }
+    });
+    assert_eq!(other.is_uring(), file.is_uring());
+}
+
+#[test]
+fn test_set_len() {
+    // Vectors are truncated and zero-extended. Vec has an inherent set_len()
+    // of its own, so call the trait method explicitly.
+    let mut v = vec![1, 2, 3, 4];
+    SetLen::set_len(&mut v, 2).unwrap();
+    assert_eq!(v, vec![1, 2]);
+    SetLen::set_len(&mut &mut v, 4).unwrap();
+    assert_eq!(v, vec![1, 2, 0, 0]);
+
+    // Slices can't truncate data after their end.
+    {
+        let mut slice = Slice::new(&mut v, 1, Some(2));
+        let err = slice.set_len(1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(slice.size().unwrap(), Some(2));
+    }
+    assert_eq!(v, vec![1, 2, 0, 0]);
+    // Lengths are clamped to the slice.
+    {
+        let mut slice = Slice::new(&mut v, 1, Some(5));
+        slice.set_len(10).unwrap();
+        assert_eq!(slice.size().unwrap(), Some(5));
+    }
+    assert_eq!(v, vec![1, 2, 0, 0, 0, 0]);
+    {
+        let mut slice = Slice::new(&mut v, 1, Some(5));
+        slice.set_len(1).unwrap();
+        assert_eq!(slice.size().unwrap(), Some(1));
+        slice.set_len(3).unwrap();
+        assert_eq!(slice.size().unwrap(), Some(1));
+    }
+    assert_eq!(v, vec![1, 2]);
+    {
+        let mut slice = Slice::new(&mut v, 1, None);
+        slice.set_len(4).unwrap();
+        assert_eq!(slice.size().unwrap(), None);
+    }
+    assert_eq!(v, vec![1, 2, 0, 0, 0]);
+
+    // Files, also through a box.
+    let file = RandomAccessFile::try_new(tempfile::tempfile().unwrap()).unwrap();
+    let mut file: Box<dyn SetLen> = Box::new(file);
+    file.set_len(100).unwrap();
+    let file = tempfile::tempfile().unwrap();
+    let mut raf = RandomAccessFile::try_new(file).unwrap();
+    raf.write_all_at(0, &[1; 10]).unwrap();
+    raf.set_len(4).unwrap();
+    assert_eq!(raf.size().unwrap(), Some(4));
+}
+
+#[test]
+fn test_allocate() {
+    // Vectors emulate allocation by writing zeros.
+    let mut v = vec![1; 6];
+    v.punch_hole(4, 10).unwrap();
+    assert_eq!(v, vec![1, 1, 1, 1, 0, 0]);
+    v.zero_range(1, 1).unwrap();
+    v.preallocate(2, 6).unwrap();
+    assert_eq!(v, vec![1, 0, 1, 1, 0, 0, 0, 0]);
+    v.zero_range(7, 2).unwrap();
+    assert_eq!(v.len(), 9);
+    assert!(v.preallocate(u64::MAX, 2).is_err());
+    #[cfg(target_pointer_width = "64")]
+    {
+        let err = v.zero_range(0, 1 << 62).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::OutOfMemory);
+        assert_eq!(v.len(), 9);
+    }
+
+    // Slices limit the range.
+    let mut v = vec![1; 6];
+    {
+        let mut slice = Slice::new(&mut v, 2, Some(3));
+        slice.zero_range(1, 10).unwrap();
+        slice.preallocate(0, 10).unwrap();
+    }
+    assert_eq!(v, vec![1, 1, 1, 0, 0, 1]);
+
+    // Files may not support every operation.
+    let mut file = RandomAccessFile::try_new(tempfile::tempfile().unwrap()).unwrap();
+    file.write_all_at(0, &[1; 8192]).unwrap();
+    let supported = |r: Result<()>| match r {
+        Ok(()) => true,
+        Err(ref e) if e.kind() == ErrorKind::Unsupported => false,
+        Err(e) => panic!("{}", e),
+    };
+    if supported(file.punch_hole(0, 4096)) {
+        assert_eq!(file.size().unwrap(), Some(8192));
+        let mut buf = [1; 4];
+        file.read_exact_at(4000, &mut buf).unwrap();
+        assert_eq!(buf, [0; 4]);
+    }
+    if supported(file.preallocate(8192, 4096)) {
+        assert_eq!(file.size().unwrap(), Some(12288));
+    }
+}
+
+#[test]
+fn test_sparse() {
+    // Without hole support, everything is data.
+    let v = vec![1; 10];
+    let extents: Vec<Extent> = v.extents().map(|e| e.unwrap()).collect();
+    assert_eq!(
+        extents,
+        vec![Extent {
+            kind: ExtentKind::Data,
+            pos: 0,
+            len: 10
+        }]
+    );
+    assert_eq!(v.allocated_size().unwrap(), Some(10));
+    let slice = Slice::new(&v, 4, Some(2));
+    assert_eq!(slice.allocated_size().unwrap(), Some(2));
+
+    // A file with data at the start and in the middle.
+    const MB: u64 = 1 << 20;
+    let mut file = RandomAccessFile::try_new(tempfile::tempfile().unwrap()).unwrap();
+    file.set_len(4 * MB).unwrap();
+    file.write_all_at(0, &[1; 4096]).unwrap();
+    file.write_all_at(2 * MB, &[2; 4096]).unwrap();
+
+    let extents: Vec<Extent> = file.extents().map(|e| e.unwrap()).collect();
+    assert_eq!(extents.iter().map(|e| e.len).sum::<u64>(), 4 * MB);
+    assert_eq!(extents[0].kind, ExtentKind::Data);
+    for pair in extents.windows(2) {
+        assert_eq!(pair[0].pos + pair[0].len, pair[1].pos);
+        assert_ne!(pair[0].kind, pair[1].kind);
+    }
+    let holes = extents.iter().any(|e| e.kind == ExtentKind::Hole);
+    if holes {
+        assert!(file.allocated_size().unwrap().unwrap() < 4 * MB);
+        assert_eq!(file.next_data(4096).unwrap(), Some(2 * MB));
+        let slice = Slice::new(&file, MB, Some(2 * MB));
+        assert_eq!(slice.next_data(0).unwrap(), Some(MB));
+        assert_eq!(slice.next_hole(MB).unwrap(), MB + 4096);
+        let slice = Slice::new(&file, MB, Some(MB));
+        assert_eq!(slice.next_data(0).unwrap(), None);
+    }
+
+    // Copying keeps holes as holes.
+    let mut copy = RandomAccessFile::try_new(tempfile::tempfile().unwrap()).unwrap();
+    let copied = copy_sparse(&file, &mut copy).unwrap();
+    assert_eq!(copy.size().unwrap(), Some(4 * MB));
+    if holes {
+        assert!(copied < MB);
+        assert!(copy.allocated_size().unwrap().unwrap() < 4 * MB);
+    }
+    let mut buf = [0; 4];
+    copy.read_exact_at(2 * MB + 4092, &mut buf).unwrap();
+    assert_eq!(buf, [2; 4]);
+    copy.read_exact_at(3 * MB, &mut buf).unwrap();
+    assert_eq!(buf, [0; 4]);
+
+    // Looking for holes doesn't move the file position.
+    let mut file = File::open("tests/pi.txt").unwrap();
+    file.seek(SeekFrom::Start(5)).unwrap();
+    assert_eq!(file.next_data(10).unwrap(), Some(10));
+    assert_eq!(file.next_hole(10).unwrap(), 1000002);
+    assert_eq!(file.stream_position().unwrap(), 5);
+
+    // Not even briefly, while other threads use it.
+    let expected = std::fs::read("tests/pi.txt").unwrap();
+    let mut file = File::open("tests/pi.txt").unwrap();
+    let searcher = file.try_clone().unwrap();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for pos in 0..1000 {
+                assert_eq!(searcher.next_data(pos).unwrap(), Some(pos));
+            }
+        });
+        let mut read = Vec::new();
+        let mut buf = [0; 7];
+        loop {
+            match file.read(&mut buf).unwrap() {
+                0 => break,
+                n => read.extend_from_slice(&buf[..n]),
+            }
+        }
+        assert_eq!(read, expected);
+    });
+
+    // Files opened only for writing can be searched too.
+    let tmp = tempfile::NamedTempFile::new().unwrap();
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .open(tmp.path())
+        .unwrap();
+    file.write_all_at(0, &[1; 10]).unwrap();
+    let extents: Vec<Extent> = file.extents().map(|e| e.unwrap()).collect();
+    assert_eq!(extents.iter().map(|e| e.len).sum::<u64>(), 10);
+    assert_eq!(file.next_data(0).unwrap(), Some(0));
+}
+
+#[test]
+fn test_copy_range() {
+    let pi = RandomAccessFile::open("tests/pi.txt").unwrap();
+    assert!(pi.as_read_file().is_some());
+
+    // Values don't need to be `Send`, and files know their size.
+    let shared = std::rc::Rc::new(File::open("tests/pi.txt").unwrap());
+    let pi = AnyIo::new_sized(shared);
+    assert!(!pi.is_writable());
+    assert_eq!(pi.size().unwrap(), Some(1_000_002));
+    assert!(Slice::new(&pi, 0, None).as_read_file().is_none());
+
+    // File to file, through the kernel where possible.
+    let mut dst = RandomAccessFile::try_new(tempfile::tempfile().unwrap()).unwrap();
+    assert_eq!(copy_range(&pi, 10, &mut dst, 4, 8).unwrap(), 8);
+    let mut buf = [0; 12];
+    dst.read_exact_at(0, &mut buf).unwrap();
+    assert_eq!(&buf, b"\x00\x00\x00\x0035897932");
+
+    // A whole file, into a plain File.
+    let mut file = tempfile::tempfile().unwrap();
+    assert_eq!(copy_range(&pi, 0, &mut file, 0, 1000002).unwrap(), 1000002);
+    let mut buf = [0; 4];
+    file.read_exact_at(764, &mut buf).unwrap();
+    assert_eq!(&buf, b"9999");
+
+    // Stops at the end of the source.
+    assert_eq!(copy_range(&pi, 999_999, &mut dst, 0, 10).unwrap(), 3);
+    assert_eq!(copy_range(&pi, 2_000_000, &mut dst, 0, 10).unwrap(), 0);
+
+    // Other objects are copied through a buffer.
+    let mut v = Vec::new();
+    assert_eq!(copy_range(&pi, 10, &mut v, 2, 4).unwrap(), 4);
+    assert_eq!(v, b"\x00\x003589");
+    let src = vec![7u8; 200_000];
+    let mut file = tempfile::tempfile().unwrap();
+    assert_eq!(
+        copy_range(&src, 100, &mut file, 0, 300_000).unwrap(),
+        199_900
+    );
+    assert_eq!(file.size().unwrap(), Some(199_900));
+
+    // Overlapping ranges of one file, through two handles.
+    #[cfg(unix)]
+    {
+        let data: Vec<u8> = (0..=255).cycle().take(300_000).collect();
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all_at(0, &data).unwrap();
+        let src = file.try_clone().unwrap();
+        let mut expected = data.clone();
+        let mut buf = vec![0; 300_000];
+        assert_eq!(
+            copy_range(&src, 0, &mut file, 100, 200_000).unwrap(),
+            200_000
+        );
+        expected.copy_within(0..200_000, 100);
+        file.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(buf, expected);
+        assert_eq!(
+            copy_range(&src, 100, &mut file, 0, 200_000).unwrap(),
+            200_000
+        );
+        expected.copy_within(100..200_100, 0);
+        file.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(buf, expected);
+        // Stops at the end of the source.
+        assert_eq!(
+            copy_range(&src, 299_990, &mut file, 299_995, 100).unwrap(),
+            10
+        );
+        assert_eq!(file.size().unwrap(), Some(300_005));
+    }
+
+    // Files opened for appending fail the same way as write_at().
+    #[cfg(target_os = "linux")]
+    {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(tmp.path())
+            .unwrap();
+        let err = copy_range(&pi, 0, &mut file, 0, 10).unwrap_err();
+        assert_eq!(err.kind(), file.write_at(0, b"x").unwrap_err().kind());
+    }
+}
+
+#[test]
+fn test_durable() {
+    let file = tempfile::tempfile().unwrap();
+    let mut slice = Slice::new(RandomAccessFile::try_new(file).unwrap(), 4096, None);
+    slice.write_all_at(0, b"data").unwrap();
+    slice.sync_range(0, 4).unwrap();
+    slice.sync_range(1 << 40, 0).unwrap();
+    slice.sync_data().unwrap();
+    slice.sync_all().unwrap();
+
+    // Still reachable through boxes and cursors.
+    let boxed: Box<dyn Durable> = Box::new(slice);
+    boxed.sync_range(0, 4096).unwrap();
+    let cursor = Cursor::new(boxed);
+    cursor.sync_all().unwrap();
+
+    let mut v = vec![1, 2, 3];
+    v.sync_all().unwrap();
+    let slice = Slice::new(&mut v[..], 1, Some(1));
+    slice.sync_range(0, 10).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_lock() {
+    let tmp = tempfile::NamedTempFile::new().unwrap();
+    let open = || {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(tmp.path())
+            .unwrap();
+        RandomAccessFile::try_new(file).unwrap()
+    };
+    let a = open();
+    let b = open();
+
+    // Shared locks coexist, exclusive ones don't.
+    let shared = a.lock_shared(0, 100).unwrap();
+    let other = b.try_lock_shared(50, 100).unwrap().unwrap();
+    assert!(b.try_lock_exclusive(0, 10).unwrap().is_none());
+    assert!(b.try_lock_exclusive(150, 10).unwrap().is_some());
+    drop(other);
+    shared.unlock().unwrap();
+    let exclusive = b.lock_exclusive(0, 0).unwrap();
+    assert!(a.try_lock_shared(1 << 40, 1).unwrap().is_none());
+    drop(exclusive);
+    assert!(a.try_lock_shared(1 << 40, 1).unwrap().is_some());
+
+    // Slices lock in their own coordinates.
+    let slice = Slice::new(&a, 1000, Some(100));
+    let lock = slice.lock_exclusive(10, 0).unwrap();
+    assert_eq!((lock.pos(), lock.end()), (1010, Some(1100)));
+    assert!(b.try_lock_shared(1099, 1).unwrap().is_none());
+    assert!(b.try_lock_shared(1100, 1).unwrap().is_some());
+    assert!(b.try_lock_shared(1005, 5).unwrap().is_some());
+    drop(lock);
+    assert!(slice.try_lock_shared(100, 1).is_err());
+    let unbounded = Slice::new(&a, 1000, None);
+    let lock = unbounded.try_lock_exclusive(0, 0).unwrap().unwrap();
+    assert_eq!(lock.end(), None);
+    assert!(b.try_lock_shared(1 << 40, 1).unwrap().is_none());
+    drop(lock);
+
+    // Guards on the same file, or its clones, don't merge.
+    let c = a.try_clone().unwrap();
+    let shared = a.lock_shared(0, 100).unwrap();
+    assert!(a.try_lock_exclusive(50, 10).unwrap().is_none());
+    assert!(c.try_lock_exclusive(50, 10).unwrap().is_none());
+    let other = c.lock_shared(0, 100).unwrap();
+    drop(other);
+    assert!(b.try_lock_exclusive(0, 10).unwrap().is_none());
+    drop(shared);
+    assert!(b.try_lock_exclusive(0, 10).unwrap().is_some());
+}
+
+#[test]
+fn test_read_buf() {
+    use std::mem::MaybeUninit;
+
+    let mut storage = [MaybeUninit::uninit(); 8];
+    let file = File::open("tests/pi.txt").unwrap();
+    let mut buf = ReadBuf::new(&mut storage);
+    assert_eq!(file.read_buf_at(10, &mut buf).unwrap(), 8);
+    assert_eq!(buf.filled(), b"35897932");
+
+    // Reads append to what was already filled.
+    let mut storage = [MaybeUninit::uninit(); 8];
+    let mut buf = ReadBuf::new(&mut storage);
+    let slice = Slice::new(&file, 10, Some(6));
+    assert_eq!(slice.read_buf_at(0, &mut buf).unwrap(), 6);
+    assert_eq!(slice.read_buf_at(4, &mut buf).unwrap(), 2);
+    assert_eq!(buf.filled(), b"35897979");
+    assert_eq!(buf.remaining(), 0);
+
+    // The default implementation zeroes first.
+    struct Ones;
+    impl ReadAt for Ones {
+        fn read_at(&self, _pos: u64, buf: &mut [u8]) -> Result<usize> {
+            assert!(buf.iter().all(|&b| b == 0));
+            buf[0] = 1;
+            Ok(1)
+        }
+    }
+    let mut storage = [MaybeUninit::uninit(); 4];
+    let mut buf = ReadBuf::new(&mut storage);
+    assert_eq!(Ones.read_buf_at(0, &mut buf).unwrap(), 1);
+    assert_eq!(Ones.read_buf_at(0, &mut buf).unwrap(), 1);
+    assert_eq!(buf.filled(), &[1, 1]);
+
+    let mut init = [0; 4];
+    let mut buf = ReadBuf::from(&mut init[..]);
+    buf.initialize_unfilled()[0] = 7;
+    buf.advance(1);
+    assert_eq!(buf.filled(), &[7]);
+
+    // Reading into a vector keeps what was there, and stops at EOF.
+    let raf = RandomAccessFile::open("tests/pi.txt").unwrap();
+    let mut v = b"pi".to_vec();
+    assert_eq!(raf.read_to_vec_at(10, 4, &mut v).unwrap(), 4);
+    assert_eq!(v, b"pi3589");
+    let mut v = Vec::new();
+    assert_eq!(raf.read_to_vec_at(0, 2_000_000, &mut v).unwrap(), 1000002);
+    assert_eq!(&v[1000000..], b"51");
+    let mut v = Vec::new();
+    assert_eq!(v.clone().read_to_vec_at(5, 10, &mut v).unwrap(), 0);
+}
+
+// Replaces the buffer it's given with a larger one, which must not let
+// callers trust the replacement's length.
+struct SwapsBuffer;
+
+impl ReadAt for SwapsBuffer {
+    fn read_at(&self, _pos: u64, buf: &mut [u8]) -> Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn read_buf_at(&self, _pos: u64, buf: &mut ReadBuf<'_>) -> Result<usize> {
+        *buf = ReadBuf::from(&mut Box::leak(vec![0; 4096].into_boxed_slice())[..]);
+        buf.append(&[1; 4096]);
+        Ok(4096)
+    }
+}
+
+#[test]
+fn test_read_to_vec_swapped_buffer() {
+    let mut v = Vec::new();
+    let err = SwapsBuffer.read_to_vec_at(0, 16, &mut v).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(v.is_empty());
+}
+
+#[test]
+fn test_slice_read_buf_swapped_buffer() {
+    let mut storage = [std::mem::MaybeUninit::uninit(); 16];
+    let mut buf = ReadBuf::new(&mut storage);
+    let err = Slice::new(SwapsBuffer, 0, Some(8))
+        .read_buf_at(0, &mut buf)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn test_read_ref() {
+    let data: Box<[u8]> = (0..10).collect();
+    assert_eq!(data.read_ref_at(2, 3).unwrap(), &[2, 3, 4]);
+    assert_eq!(data.read_ref_at(8, 3).unwrap(), &[8, 9]);
+    assert!(data.read_ref_at(20, 3).unwrap().is_empty());
+
+    // Borrowed through slices and references, without copying.
+    let slice = Slice::new(&data, 5, Some(3));
+    let bytes = slice.read_ref_at(1, 10).unwrap();
+    assert_eq!(bytes, &[6, 7]);
+    assert_eq!(bytes.as_ptr(), data[6..].as_ptr());
+    let mut array = [1u8, 2, 3];
+    let boxed: Box<dyn ReadAtRef> = Box::new(&mut array[..]);
+    assert_eq!(boxed.read_ref_at(1, 1).unwrap(), &[2]);
+
+    let v = vec![1, 2, 3];
+    match Slice::new(&v, 1, None).read_cow_at(0, 4).unwrap() {
+        Cow::Borrowed(bytes) => assert_eq!(bytes, &[2, 3]),
+        Cow::Owned(_) => panic!("vector contents should be borrowed"),
+    }
+
+    // Files are copied.
+    let file = File::open("tests/pi.txt").unwrap();
+    match file.read_cow_at(10, 4).unwrap() {
+        Cow::Owned(bytes) => assert_eq!(bytes, b"3589"),
+        Cow::Borrowed(_) => panic!("file contents can't be borrowed"),
+    }
+    assert_eq!(&*file.read_cow_at(1000000, 4).unwrap(), b"51");
+}
+
+#[cfg(all(feature = "error-context", feature = "byteorder"))]
+#[test]
+fn test_error_context() {
+    use byteorder::BigEndian;
+    use positioned_io::PositionedError;
+    use std::error::Error as _;
+
+    let raf = RandomAccessFile::open("tests/pi.txt").unwrap();
+    let slice = Slice::new(ByteIo::<_, BigEndian>::new(raf), 999_990, None);
+    let mut buf = [0; 16];
+    let err = slice.read_exact_at(4, &mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    let outer = PositionedError::find(&err).unwrap();
+    assert_eq!(outer.layer(), "ReadAt::read_exact_at");
+    assert_eq!((outer.pos(), outer.len(), outer.transferred()), (4, 16, 8));
+    assert!(outer.to_string().contains("at offset 4"));
+    assert!(outer.source().is_some());
+
+    // Errors from inner layers are wrapped by each layer they pass through.
+    let read_only = File::open("tests/pi.txt").unwrap();
+    let mut slice = Slice::new(ByteIo::<_, BigEndian>::new(read_only), 100, None);
+    let err = slice.write_all_at(5, b"x").unwrap_err();
+    let outer = PositionedError::downcast(err).unwrap();
+    assert_eq!(outer.layer(), "WriteAt::write_all_at");
+    let slice_err = PositionedError::find(outer.get_ref()).unwrap();
+    assert_eq!(
+        (slice_err.layer(), slice_err.pos()),
+        ("Slice::write_at", 105)
+    );
+    let byteio_err = PositionedError::find(slice_err.get_ref()).unwrap();
+    assert_eq!(byteio_err.layer(), "ByteIo::write_at");
+    assert!(PositionedError::find(byteio_err.get_ref()).is_none());
+
+    // And still convert back.
+    let err: Error = outer.into();
+    assert!(PositionedError::find(&err).is_some());
+
+    let mut cursor = Cursor::new_pos(File::open("tests/pi.txt").unwrap(), 7);
+    let err = cursor.write(b"x").unwrap_err();
+    let context = PositionedError::find(&err).unwrap();
+    assert_eq!((context.layer(), context.pos()), ("Cursor::write", 7));
+}
+
+// A backend with its own error type, that fails at a given offset.
+#[derive(Debug, PartialEq)]
+enum FlashError {
+    BadBlock(u64),
+    Io(ErrorKind),
+}
+
+impl From<ErrorKind> for FlashError {
+    fn from(kind: ErrorKind) -> Self {
+        FlashError::Io(kind)
+    }
+}
+
+impl From<FlashError> for Error {
+    fn from(err: FlashError) -> Self {
+        Error::other(format!("{:?}", err))
+    }
+}
+
+struct Flash {
+    data: Vec<u8>,
+    bad: u64,
+}
+
+impl ErrorType for Flash {
+    type Error = FlashError;
+}
+
+impl TryReadAt for Flash {
+    fn try_read_at(&self, pos: u64, buf: &mut [u8]) -> std::result::Result<usize, FlashError> {
+        if pos == self.bad {
+            return Err(FlashError::BadBlock(pos));
+        }
+        Ok(self.data.as_slice().read_at(pos, buf).unwrap())
+    }
+}
+
+impl TryWriteAt for Flash {
+    fn try_write_at(&mut self, pos: u64, buf: &[u8]) -> std::result::Result<usize, FlashError> {
+        if pos == self.bad {
+            return Err(FlashError::BadBlock(pos));
+        }
+        Ok((&mut self.data[..]).write_at(pos, buf).unwrap())
+    }
+
+    fn try_flush(&mut self) -> std::result::Result<(), FlashError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_try_io() {
+    let flash = Flash {
+        data: (0..16).collect(),
+        bad: 10,
+    };
+
+    // Slices translate offsets, and pass typed errors through.
+    let mut slice = Slice::new(flash, 4, Some(8));
+    let mut buf = [0; 4];
+    slice.try_read_exact_at(2, &mut buf).unwrap();
+    assert_eq!(buf, [6, 7, 8, 9]);
+    assert_eq!(
+        slice.try_read_at(6, &mut buf),
+        Err(FlashError::BadBlock(10))
+    );
+    assert_eq!(
+        slice.try_read_exact_at(7, &mut buf),
+        Err(FlashError::Io(ErrorKind::UnexpectedEof))
+    );
+    slice.try_write_all_at(0, &[9, 9]).unwrap();
+    assert_eq!(
+        slice.try_write_all_at(7, &[1, 2]),
+        Err(FlashError::Io(ErrorKind::WriteZero))
+    );
+    let mut slice = Slice::new(slice.into_inner(), u64::MAX, None);
+    assert_eq!(
+        slice.try_write_at(1, &[0]),
+        Err(FlashError::Io(ErrorKind::InvalidInput))
+    );
+
+    // Cursors keep the error type too.
+    let mut cursor = Cursor::new_pos(slice.into_inner(), 8);
+    cursor.try_read_exact(&mut buf[..2]).unwrap();
+    assert_eq!(buf[..2], [8, 9]);
+    assert_eq!(cursor.try_write(&[0]), Err(FlashError::BadBlock(10)));
+    assert_eq!(cursor.position(), 10);
+
+    // Bridge into io::Error.
+    let io = IoAdapter::new(cursor.into_inner());
+    assert_eq!(io.read_at(4, &mut buf).unwrap(), 4);
+    assert_eq!(buf, [9, 9, 6, 7]);
+    let err = io.read_at(10, &mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Other);
+    assert_eq!(err.to_string(), "BadBlock(10)");
+
+    // And back again.
+    let mut v = TryAdapter::new(vec![0; 4]);
+    v.try_write_all_at(2, &[1, 2, 3]).unwrap();
+    assert_eq!(v.get_ref(), &vec![0, 0, 1, 2, 3]);
+    let err = Slice::new(&v, u64::MAX, None)
+        .try_read_at(1, &mut buf)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[cfg(feature = "byteorder")]
+#[test]
+fn test_try_io_byteio() {
+    let flash = Flash {
+        data: (0..16).collect(),
+        bad: 3,
+    };
+    let mut io = ByteIo::<_, LittleEndian>::new(flash);
+    let mut buf = [0; 2];
+    io.try_read_exact_at(1, &mut buf).unwrap();
+    assert_eq!(buf, [1, 2]);
+    assert_eq!(io.try_write_at(3, &buf), Err(FlashError::BadBlock(3)));
+}
+
+#[test]
+fn test_write_shared() {
+    use std::{
+        sync::{Mutex, RwLock},
+        thread,
+    };
+
+    // Workers write through one shared file, without a lock.
+    let raf = Arc::new(RandomAccessFile::try_new(tempfile::tempfile().unwrap()).unwrap());
+    let workers: Vec<_> = (0..4u8)
+        .map(|i| {
+            let raf = Arc::clone(&raf);
+            thread::spawn(move || {
+                let mut chunk = Slice::new(raf, u64::from(i) * 100, Some(100));
+                chunk.write_all_at(0, &[i; 100]).unwrap();
+                assert_eq!(chunk.write_at(99, &[i; 2]).unwrap(), 1);
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    let mut buf = [0; 400];
+    raf.read_exact_at(0, &mut buf).unwrap();
+    assert!(buf.chunks(100).zip(0..).all(|(c, i)| c == [i; 100]));
+
+    // Anything behind a lock can be shared too.
+    let locked = Mutex::new(vec![0; 4]);
+    let slice = Slice::new(&locked, 2, None);
+    positioned_io::WriteAtShared::write_all_at(&slice, 1, &[7, 8]).unwrap();
+    assert_eq!(*locked.lock().unwrap(), vec![0, 0, 0, 7, 8]);
+
+    let locked = RwLock::new(vec![0; 2]);
+    let mut io = &locked;
+    io.write_all_at(1, &[5]).unwrap();
+    assert_eq!(*locked.read().unwrap(), vec![0, 5]);
+
+    // Files can be written through a shared reference, except on Windows.
+    #[cfg(unix)]
+    {
+        let file = tempfile::tempfile().unwrap();
+        let mut cursor = Cursor::new_pos(&file, 3);
+        cursor.write_all(b"abc").unwrap();
+        let mut buf = [0; 6];
+        file.read_exact_at(0, &mut buf).unwrap();
+        assert_eq!(&buf, b"\x00\x00\x00abc");
+        assert!(positioned_io::WriteAtShared::as_write_file(&file).is_some());
+    }
+}
+
+#[test]
+fn test_containers() {
+    use std::{
+        collections::VecDeque,
+        rc::Rc,
+        sync::{Mutex, RwLock},
+    };
+
+    fn read<R: ReadAt + Size + ?Sized>(io: &R, pos: u64, len: usize) -> Vec<u8> {
+        let mut buf = vec![0; len];
+        let n = io.read_at(pos, &mut buf).unwrap();
+        buf.truncate(n);
+        buf
+    }
+
+    // Shared and locked objects forward to what they hold.
+    let arc = Arc::new(vec![1, 2, 3]);
+    assert_eq!(read(&arc, 1, 4), [2, 3]);
+    assert_eq!(arc.size().unwrap(), Some(3));
+    let rc = Rc::new(vec![1, 2, 3]);
+    assert_eq!(read(&rc, 2, 4), [3]);
+    assert_eq!(rc.read_ref_at(0, 2).unwrap(), [1, 2]);
+
+    let mut mutex = Mutex::new(vec![1, 2]);
+    mutex.write_all_at(3, &[4]).unwrap();
+    assert_eq!(read(&mutex, 0, 8), [1, 2, 0, 4]);
+    assert_eq!(mutex.size().unwrap(), Some(4));
+    let mut rwlock = RwLock::new(vec![1, 2]);
+    rwlock.write_all_at(1, &[5, 6]).unwrap();
+    assert_eq!(read(&rwlock, 0, 8), [1, 5, 6]);
+
+    // Byte containers with a fixed size truncate writes at the end.
+    let mut array = [0; 4];
+    assert_eq!(array.write_at(2, &[7, 8, 9]).unwrap(), 2);
+    assert_eq!(array, [0, 0, 7, 8]);
+    assert_eq!(read(&array, 3, 4), [8]);
+    assert_eq!(array.size().unwrap(), Some(4));
+    assert_eq!(array.write_at(4, &[1]).unwrap(), 0);
+
+    let mut boxed: Box<[u8]> = Box::new([1, 2, 3]);
+    assert_eq!(boxed.write_at(1, &[7, 8, 9]).unwrap(), 2);
+    assert_eq!(&*boxed, [1, 7, 8]);
+
+    let shared: Arc<[u8]> = Arc::from(&[1, 2, 3][..]);
+    assert_eq!(read(&shared, 1, 1), [2]);
+    assert_eq!(shared.read_ref_at(1, 8).unwrap(), [2, 3]);
+    assert_eq!(shared.size().unwrap(), Some(3));
+    let shared: Rc<[u8]> = Rc::from(&[1, 2, 3][..]);
+    assert_eq!(read(&shared, 0, 8), [1, 2, 3]);
+
+    // A Cow only copies on the first write, and then grows like a Vec.
+    let data = [1, 2, 3];
+    let mut cow = Cow::Borrowed(&data[..]);
+    assert_eq!(read(&cow, 1, 8), [2, 3]);
+    assert!(matches!(cow.read_cow_at(0, 1).unwrap(), Cow::Borrowed([1])));
+    cow.write_all_at(4, &[5]).unwrap();
+    assert!(matches!(cow, Cow::Owned(_)));
+    assert_eq!(&*cow, [1, 2, 3, 0, 5]);
+    assert_eq!(data, [1, 2, 3]);
+    SetLen::set_len(&mut cow, 2).unwrap();
+    assert_eq!(cow.size().unwrap(), Some(2));
+
+    // Strings are read-only.
+    let string = String::from("hello");
+    assert_eq!(read(&string, 1, 3), b"ell");
+    assert_eq!(read("hello", 3, 8), b"lo");
+    assert_eq!("hello".read_ref_at(4, 8).unwrap(), b"o");
+    assert_eq!(string.size().unwrap(), Some(5));
+
+    // Reads and writes on a VecDeque span both its halves.
+    let mut deque: VecDeque<u8> = VecDeque::with_capacity(8);
+    deque.extend([3, 4, 5, 6]);
+    deque.push_front(2);
+    deque.push_front(1);
+    assert!(!deque.as_slices().1.is_empty());
+    assert_eq!(read(&deque, 0, 8), [1, 2, 3, 4, 5, 6]);
+    assert_eq!(read(&deque, 1, 3), [2, 3, 4]);
+    assert_eq!(read(&deque, 4, 3), [5, 6]);
+    assert_eq!(read(&deque, 7, 3), []);
+    deque.write_all_at(1, &[7, 8, 9]).unwrap();
+    assert_eq!(deque, [1, 7, 8, 9, 5, 6]);
+    deque.write_all_at(5, &[10, 11]).unwrap();
+    assert_eq!(deque, [1, 7, 8, 9, 5, 10, 11]);
+    deque.write_all_at(9, &[12]).unwrap();
+    assert_eq!(deque, [1, 7, 8, 9, 5, 10, 11, 0, 0, 12]);
+    assert_eq!(deque.size().unwrap(), Some(10));
+    SetLen::set_len(&mut deque, 3).unwrap();
+    assert_eq!(deque, [1, 7, 8]);
+
+    // A standard cursor reads and writes at offsets, keeping its position.
+    let mut cursor = std::io::Cursor::new(vec![1, 2, 3]);
+    cursor.set_position(2);
+    assert_eq!(read(&cursor, 0, 2), [1, 2]);
+    cursor.write_all_at(3, &[4]).unwrap();
+    assert_eq!(cursor.get_ref(), &[1, 2, 3, 4]);
+    assert_eq!(cursor.position(), 2);
+    assert_eq!(cursor.size().unwrap(), Some(4));
+}
+
+#[test]
+fn test_read_at_ext() {
+    // A source of unknown size, that only returns a few bytes at a time.
+    struct Trickle(Vec<u8>);
+    impl ReadAt for Trickle {
+        fn read_at(&self, pos: u64, buf: &mut [u8]) -> Result<usize> {
+            let len = buf.len().min(3);
+            self.0.read_at(pos, &mut buf[..len])
+        }
+    }
+    impl Size for Trickle {
+        fn size(&self) -> Result<Option<u64>> {
+            Ok(None)
+        }
+    }
+
+    let file = File::open("tests/pi.txt").unwrap();
+    assert_eq!(&file.read_array_at::<4>(10).unwrap(), b"3589");
+    assert_eq!(file.read_vec_at(2, 5).unwrap(), b"14159");
+    let err = file.read_vec_at(999_990, 20).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    let err = file.read_array_at::<20>(999_990).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+    // Short reads are retried, but the end of file isn't an error.
+    let trickle = Trickle((0..20).collect());
+    let mut buf = [0; 8];
+    assert_eq!(trickle.read_at_most(4, &mut buf).unwrap(), 8);
+    assert_eq!(buf, [4, 5, 6, 7, 8, 9, 10, 11]);
+    assert_eq!(trickle.read_at_most(16, &mut buf).unwrap(), 4);
+    assert_eq!(trickle.read_at_most(30, &mut buf).unwrap(), 0);
+    let interrupt = ReadCustom::new(&file, || {
+        Err(Error::new(ErrorKind::Interrupted, "interrupt!"))
+    });
+    assert_eq!(interrupt.read_at_most(999_998, &mut buf).unwrap(), 4);
+    let fail = ReadCustom::new(&file, || Err(Error::other("random fail")));
+    assert!(fail.read_at_most(0, &mut buf).is_err());
+
+    // Read to the end, with and without a known size.
+    let mut vec = vec![1];
+    assert_eq!(file.read_to_end_at(999_990, &mut vec).unwrap(), 12);
+    assert_eq!(vec[1..], std::fs::read("tests/pi.txt").unwrap()[999_990..]);
+    let mut vec = Vec::new();
+    assert_eq!(file.read_to_end_at(2_000_000, &mut vec).unwrap(), 0);
+    let mut vec = Vec::new();
+    assert_eq!(file.read_to_end_at(0, &mut vec).unwrap(), 1_000_002);
+    assert_eq!(vec, std::fs::read("tests/pi.txt").unwrap());
+    let mut vec = Vec::new();
+    assert_eq!(file.read_to_end_sized_at(0, &mut vec).unwrap(), 1_000_002);
+    assert_eq!(vec, std::fs::read("tests/pi.txt").unwrap());
+    // Finding the end doesn't grow the vector.
+    assert!(vec.capacity() < 1_000_002 + 4096);
+    let mut vec = vec![1];
+    assert_eq!(file.read_to_end_sized_at(999_990, &mut vec).unwrap(), 12);
+    assert_eq!(vec[1..], std::fs::read("tests/pi.txt").unwrap()[999_990..]);
+
+    let big = Trickle((0..20_000u32).map(|i| i as u8).collect());
+    let mut vec = Vec::new();
+    assert_eq!(big.read_to_end_at(5, &mut vec).unwrap(), 19_995);
+    assert_eq!(vec, big.0[5..]);
+    let mut vec = Vec::new();
+    assert_eq!(big.read_to_end_sized_at(5, &mut vec).unwrap(), 19_995);
+    assert_eq!(vec, big.0[5..]);
+
+    // Sources that don't know their size are read too.
+    let unsized_big = ReadCustom::new(&big, || {
+        Err(Error::new(ErrorKind::Interrupted, "interrupt!"))
+    });
+    let mut vec = Vec::new();
+    assert_eq!(unsized_big.read_to_end_at(0, &mut vec).unwrap(), 20_000);
+    assert_eq!(vec, big.0);
+}
+
+#[test]
+fn test_geometry() {
+    use std::collections::VecDeque;
+
+    // In-memory objects have no blocks to speak of, but differ in growth.
+    let mut vec = vec![0; 8];
+    let caps = vec.geometry().unwrap();
+    assert_eq!(caps.logical_block_size, 1);
+    assert!(caps.extendable && !caps.holes && !caps.concurrent_writes);
+    assert!(VecDeque::<u8>::new().geometry().unwrap().extendable);
+    let slice: &mut [u8] = &mut vec;
+    assert!(!slice.geometry().unwrap().extendable);
+    assert!(![0u8; 4].geometry().unwrap().extendable);
+    assert!(!Box::new(vec.as_slice()).geometry().unwrap().extendable);
+
+    // Locks make concurrent writes possible.
+    let locked = std::sync::Mutex::new(vec![0]);
+    assert!(locked.geometry().unwrap().concurrent_writes);
+
+    let file = tempfile::tempfile().unwrap();
+    let caps = file.geometry().unwrap();
+    assert!(caps.logical_block_size.is_power_of_two());
+    assert!(caps.physical_block_size >= caps.logical_block_size);
+    assert!(caps.preferred_io_size.is_some());
+    assert!(caps.extendable && caps.concurrent_writes);
+    if let Some(dio) = caps.direct_io_alignment {
+        assert!(dio.memory > 0 && dio.offset > 0);
+    }
+    let raf = RandomAccessFile::try_new(file).unwrap();
+    assert_eq!(raf.geometry().unwrap(), caps);
+
+    // Slices are only as aligned as their offset, and may not grow.
+    let block = caps.logical_block_size;
+    let aligned = Slice::new(&raf, block * 2, None);
+    assert_eq!(aligned.geometry().unwrap(), caps);
+    let unaligned = Slice::new(&raf, block + 2, Some(10));
+    let slice_caps = unaligned.geometry().unwrap();
+    assert_eq!(slice_caps.logical_block_size, 2);
+    assert_eq!(slice_caps.direct_io_alignment, None);
+    assert!(!slice_caps.extendable);
+}
+
+#[test]
+fn test_advise() {
+    // Remembers the advice it was given.
+    #[derive(Default)]
+    struct Advised(RefCell<Vec<(u64, u64, Advice)>>);
+    impl ReadAt for Advised {
+        fn read_at(&self, _pos: u64, _buf: &mut [u8]) -> Result<usize> {
+            Ok(0)
+        }
+
+        fn advise(&self, pos: u64, len: u64, advice: Advice) -> Result<()> {
+            self.0.borrow_mut().push((pos, len, advice));
+            Ok(())
+        }
+    }
+
+    // Slices translate and clamp the range.
+    let advised = Advised::default();
+    let slice = Slice::new(&advised, 10, Some(100));
+    slice.advise(5, 0, Advice::DontNeed).unwrap();
+    slice.advise(5, 200, Advice::WillNeed).unwrap();
+    slice.advise(20, 30, Advice::Sequential).unwrap();
+    slice.advise(150, 1, Advice::WillNeed).unwrap();
+    Slice::new(&advised, 10, None)
+        .advise(5, 0, Advice::Random)
+        .unwrap();
+    let boxed: Box<dyn ReadAt> = Box::new(Slice::new(&advised, 1, None));
+    boxed.advise(1, 1, Advice::NoReuse).unwrap();
+    assert_eq!(
+        *advised.0.borrow(),
+        [
+            (15, 95, Advice::DontNeed),
+            (15, 95, Advice::WillNeed),
+            (30, 30, Advice::Sequential),
+            (15, 0, Advice::Random),
+            (2, 1, Advice::NoReuse),
+        ]
+    );
+    let err = Slice::new(&advised, u64::MAX, None)
+        .advise(1, 1, Advice::Normal)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // Memory ignores advice, files pass it on.
+    vec![0; 4].advise(0, 0, Advice::WillNeed).unwrap();
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(&[1; 8192]).unwrap();
+    let raf = RandomAccessFile::try_new(file).unwrap();
+    for advice in [
+        Advice::Normal,
+        Advice::Random,
+        Advice::Sequential,
+        Advice::WillNeed,
+        Advice::DontNeed,
+        Advice::NoReuse,
+    ] {
+        raf.advise(0, 4096, advice).unwrap();
+        raf.advise(4096, 0, advice).unwrap();
+    }
+    assert_eq!(raf.read_vec_at(4000, 200).unwrap(), [1; 200]);
+}
+
+#[test]
+fn test_any_io() {
+    // Plugins hand out storage without saying what it supports.
+    let mut ios = vec![
+        AnyIo::new(vec![1, 2, 3]),
+        AnyIo::new_rw(RandomAccessFile::try_new(tempfile::tempfile().unwrap()).unwrap()),
+        AnyIo::new_read_only(File::open("tests/pi.txt").unwrap()),
+    ];
+    let caps: Vec<_> = ios
+        .iter_mut()
+        .map(|io| (io.as_write_at().is_some(), io.as_size().is_some()))
+        .collect();
+    assert_eq!(caps, [(true, true), (true, false), (false, false)]);
+    assert!(ios[1].is_writable() && !ios[2].is_writable());
+
+    // Missing capabilities show up as errors or unknown sizes.
+    let mut pi = ios.pop().unwrap();
+    assert_eq!(&pi.read_array_at::<4>(10).unwrap(), b"3589");
+    assert_eq!(pi.size().unwrap(), None);
+    let err = pi.write_at(0, b"x").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+    pi.flush().unwrap();
+    assert!(pi.as_read_file().is_some());
+
+    // Values don't need to be `Send`, and files know their size.
+    let shared = std::rc::Rc::new(File::open("tests/pi.txt").unwrap());
+    let pi = AnyIo::new_sized(shared);
+    assert!(!pi.is_writable());
+    assert_eq!(pi.size().unwrap(), Some(1_000_002));
+
+    // The original value can be recovered.
+    let raf = ios.pop().unwrap();
+    assert!(raf.is::<RandomAccessFile>());
+    let raf = raf.downcast::<Vec<u8>>().unwrap_err();
+    let raf: RandomAccessFile = raf.downcast().unwrap();
+    assert_eq!(raf.size().unwrap(), Some(0));
+
+    let mut vec = ios.pop().unwrap();
+    vec.write_all_at(4, &[5]).unwrap();
+    assert_eq!(vec.size().unwrap(), Some(5));
+    vec.downcast_mut::<Vec<u8>>().unwrap().push(6);
+    assert_eq!(vec.downcast_ref::<Vec<u8>>().unwrap(), &[1, 2, 3, 0, 5, 6]);
+    assert!(vec.downcast_ref::<File>().is_none());
+    assert_eq!(
+        format!("{:?}", vec),
+        "AnyIo { writable: true, sized: true, .. }"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_append_mode() {
+    use std::fs::OpenOptions;
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(temp.path(), b"0123456789").unwrap();
+    let open = || {
+        OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(temp.path())
+            .unwrap()
+    };
+    let expect_invalid = |result: Result<usize>| {
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidInput);
+    };
+
+    // Writes would land at the end, so they're refused.
+    let mut file = open();
+    expect_invalid(file.write_at(2, b"ab"));
+    expect_invalid(positioned_io::WriteAtShared::write_at(&file, 2, b"ab"));
+    expect_invalid(file.write_vectored_at(2, &[IoSlice::new(b"ab")]));
+    assert!(file.write_all_at(2, b"ab").is_err());
+
+    // Reads are fine.
+    let raf = RandomAccessFile::try_new(open()).unwrap();
+    assert_eq!(&raf.read_array_at::<3>(2).unwrap(), b"234");
+    expect_invalid((&raf).write_at(2, b"ab"));
+    expect_invalid((&raf).write_vectored_at(2, &[IoSlice::new(b"ab")]));
+    let mut slice = Slice::new(raf, 1, None);
+    expect_invalid(slice.write_at(1, b"ab"));
+
+    #[cfg(feature = "io-uring")]
+    {
+        let uring = positioned_io::UringFile::try_new(open()).unwrap();
+        let results = uring.write_many_at(&[(0, b"a"), (1, b"b")]);
+        assert!(results.iter().all(|r| r.is_err()));
+    }
+
+    // Appending to the file still works as usual.
+    file.write_all(b"!").unwrap();
+    assert_eq!(std::fs::read(temp.path()).unwrap(), b"0123456789!");
+
+    // Files without O_APPEND are unaffected.
+    let mut file = OpenOptions::new().write(true).open(temp.path()).unwrap();
+    file.write_all_at(2, b"ab").unwrap();
+    assert_eq!(std::fs::read(temp.path()).unwrap(), b"01ab456789!");
+
+    // Setting O_APPEND later is noticed too.
+    let mut raf = RandomAccessFile::try_new(file).unwrap();
+    {
+        use std::os::unix::io::AsRawFd;
+        let fd = raf.as_raw_fd();
+        assert_eq!(unsafe { libc::fcntl(fd, libc::F_SETFL, libc::O_APPEND) }, 0);
+    }
+    expect_invalid(raf.write_at(0, b"x"));
+}
+
+#[test]
+fn test_stable_reader() {
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(temp.path(), b"0123456789").unwrap();
+    let writer = std::fs::OpenOptions::new()
+        .write(true)
+        .open(temp.path())
+        .unwrap();
+
+    let mut reader = StableReader::new(RandomAccessFile::open(temp.path()).unwrap()).unwrap();
+    assert_eq!(&reader.read_array_at::<4>(2).unwrap(), b"2345");
+    reader.check().unwrap();
+
+    // Truncation is noticed on the next read.
+    writer.set_len(5).unwrap();
+    let err = reader.read_exact_at(0, &mut [0; 2]).unwrap_err();
+    assert_eq!(ConcurrentModification::find(&err).unwrap().what(), "size");
+    assert!(reader.check().is_err());
+    let results = reader.read_many_at(&mut [(0, &mut [0; 1][..]), (1, &mut [0; 1][..])]);
+    assert!(
+        results
+            .iter()
+            .all(|r| ConcurrentModification::find(r.as_ref().unwrap_err()).is_some())
+    );
+
+    // Until the reader is refreshed.
+    reader.refresh().unwrap();
+    assert_eq!(&reader.read_array_at::<5>(0).unwrap(), b"01234");
+
+    // Rewrites in place change the modification time.
+    let mut reader = reader.check_every(3);
+    (&writer).write_all_at(0, b"abc").unwrap();
+    writer
+        .set_modified(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert_eq!(&reader.read_array_at::<2>(0).unwrap(), b"ab");
+    assert_eq!(&reader.read_array_at::<2>(2).unwrap(), b"c3");
+    let err = reader.read_array_at::<1>(4).unwrap_err();
+    assert!(ConcurrentModification::find(&err).is_some());
+    reader.refresh().unwrap();
+    reader.read_array_at::<1>(4).unwrap();
+
+    // With no interval, only explicit checks notice changes.
+    let reader = reader.check_every(0);
+    writer.set_len(10).unwrap();
+    reader.read_array_at::<10>(0).unwrap();
+    assert!(reader.check().is_err());
+    assert!(ConcurrentModification::find(&Error::other("size")).is_none());
+
+    // Only files can be checked.
+    let err = StableReader::new(vec![0; 4]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+}
+
+#[test]
+fn test_random_access_file_options() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("options.bin");
+
+    // Nothing to open yet.
+    let err = RandomAccessFile::options()
+        .read(true)
+        .open(&path)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    let mut raf = RandomAccessFile::options()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .preallocate(4096)
+        .open(&path)
+        .unwrap();
+    assert_eq!(raf.size().unwrap(), Some(4096));
+    raf.write_all_at(100, b"hello").unwrap();
+    assert_eq!(&raf.read_array_at::<5>(100).unwrap(), b"hello");
+    drop(raf);
+
+    let err = RandomAccessFile::options()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+    // Preallocation doesn't shrink a longer file.
+    let raf = RandomAccessFile::options()
+        .read(true)
+        .write(true)
+        .preallocate(10)
+        .advice(Some(Advice::Sequential))
+        .open(&path)
+        .unwrap();
+    assert_eq!(raf.size().unwrap(), Some(4096));
+    assert_eq!(&raf.read_array_at::<5>(100).unwrap(), b"hello");
+
+    let raf = RandomAccessFile::options()
+        .write(true)
+        .truncate(true)
+        .advice(None)
+        .open(&path)
+        .unwrap();
+    assert_eq!(raf.size().unwrap(), Some(0));
+
+    #[cfg(target_os = "linux")]
+    {
+        let mut raf = RandomAccessFile::options()
+            .read(true)
+            .write(true)
+            .noatime(true)
+            .dsync(true)
+            .open(&path)
+            .unwrap();
+        raf.write_all_at(0, b"synced").unwrap();
+        assert_eq!(&raf.read_array_at::<6>(0).unwrap(), b"synced");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_random_access_file_handles() {
+    use std::os::unix::io::{AsFd, AsRawFd, OwnedFd};
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    let raf = RandomAccessFile::options()
+        .read(true)
+        .write(true)
+        .open(temp.path())
+        .unwrap();
+    assert_eq!(raf.metadata().unwrap().len(), 0);
+
+    // Clones share the file.
+    let clone = raf.try_clone().unwrap();
+    assert_ne!(clone.as_raw_fd(), raf.as_raw_fd());
+    positioned_io::WriteAtShared::write_all_at(&clone, 0, b"shared").unwrap();
+    assert_eq!(&raf.read_array_at::<6>(0).unwrap(), b"shared");
+
+    // Borrowed and owned descriptors work like the file itself.
+    let file = File::from(raf.as_fd().try_clone_to_owned().unwrap());
+    assert_eq!(file.metadata().unwrap().len(), 6);
+    let fd = OwnedFd::from(clone);
+    let raf = RandomAccessFile::try_from(fd).unwrap();
+    assert_eq!(&raf.read_array_at::<6>(0).unwrap(), b"shared");
+
+    // An Arc can be written from several threads at once.
+    let raf = Arc::new(raf);
+    let threads: Vec<_> = (0..4u8)
+        .map(|i| {
+            let mut raf = Arc::clone(&raf);
+            std::thread::spawn(move || raf.write_all_at(u64::from(i) * 2, &[b'0' + i; 2]))
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap().unwrap();
+    }
+    assert_eq!(&raf.read_array_at::<8>(0).unwrap(), b"00112233");
+}